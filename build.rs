@@ -9,4 +9,15 @@ fn main() {
 }
 
 #[cfg(unix)]
-fn main() {}
+fn main() {
+    // Тест сигналов logind запускает свой dbus-daemon. Если его нет, тест
+    // пропускается
+    println!("cargo::rustc-check-cfg=cfg(dbus_daemon)");
+    println!("cargo::rerun-if-env-changed=PATH");
+    let has_dbus_daemon = std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| dir.join("dbus-daemon").is_file())
+    });
+    if has_dbus_daemon {
+        println!("cargo::rustc-cfg=dbus_daemon");
+    }
+}
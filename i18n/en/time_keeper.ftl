@@ -3,6 +3,22 @@
 
 work_notification = Yay! I have to work again!
 break_notification = It's time to take a break
+long_break_notification = It's time for a long break. Get up and walk around
+break_warning_notification = A break begins in { $mins } min. Finish what you are doing
+break_final_warning_notification = A break begins in { $secs } seconds
+daily_limit_notification = You have worked { $percent }% of your daily limit. { $mins } min. left
+daily_limit_reached_notification = The daily work limit is reached. Time to call it a day!
+phase_notification = Time for "{ $name }"
+sequence_finished_notification = The phase sequence is over. The timer is stopped
+pause_over_notification = The pause "{ $reason }" is over. The timer goes on
+intervals_adjusted_notification = The intervals were adjusted by your statistics: work { $work }, break { $free }
+break_ignored_notification = The break is still going on. Step away from the computer
+micro_break_notification = Look at something far away for { $secs } seconds
+
+# Durations
+time_hours = { $value }h
+time_mins = { $value }m
+time_secs = { $value }s
 
 #############
 # MAIN PAGE #
@@ -11,16 +27,42 @@ break_notification = It's time to take a break
 # Labels
 work = Work
 break = Break
+long_break = Long break
+micro_break = Rest your eyes
+off_hours = Off hours
 
 # Control buttons
 start = Start
 pause = Pause
 stop = Stop
+postpone = Postpone
+break_now = Break now
+break_now_tooltip = End the work phase and take a break right now (F8)
+end_break = End break
+end_break_tooltip = End the break and resume work (F8)
+extend = +5 min
+extend_tooltip = Make the current phase 5 minutes longer (F9)
+emergency_skip_placeholder = Type the emergency phrase and press Enter
+pause_reason = Pause for...
+pause_reason_tooltip = Pause the timer with a reason. A timed pause ends on its own
+pause_meeting = Meeting (30 min)
+pause_lunch = Lunch (1 h)
+pause_manual = Until I resume
+paused_for = { $reason }: resumes in { $time }
+profile_custom = My settings
+profile_tooltip = Intervals profile. "My settings" are set on the Preferences page
+postpone_tooltip = Postpone the break by { $mins } min. (F7). Left: { $left }
 
 # Statistics subpage
 stats_date = Date:
 stats_type = Type:
 stats_duration = Duration:
+stats_profile = Profile:
+stats_outcome = Outcome:
+outcome_finished = Finished
+outcome_skipped = Ended early
+outcome_stopped = Stopped
+outcome_postponed = Postponed
 empty_stats = Statistics are empty
 stats_export = Export CSV
 stats_file_locate = File will be saved to ~/TimeKeeper-statistics.csv
@@ -30,10 +72,27 @@ ten_cycles = The last 10 cycles are shown
 # Footer buttons
 preferences = Preferences
 about = About
+dashboard = Dashboard
 show-stats = Show statistics
 hide-stats = Hide statistics
 
 
+##################
+# DASHBOARD PAGE #
+dashboard_range = Show:
+dashboard_days = { $days } days
+chart_days = Work and breaks by day
+chart_hours = Work by hour of the day
+chart_calendar = Work calendar
+chart_day_tooltip =
+    { $date }
+    Work: { $work }
+    Breaks: { $breaks }
+    Cycles: { $cycles }
+chart_hour_tooltip = { $hour }:00-{ $next }:00: { $work }
+chart_calendar_tooltip = { $date }: { $work }
+
+
 ##############
 # ABOUT PAGE #
 ##############
@@ -59,6 +118,7 @@ pref_break_alerts = Break alerts
 pref_notifications = Notifications
 pref_save = Save
 pref_close = OK
+pref_off = Off
 
 pref_notifications_tooltip = If the checkbox is selected, TimeKeeper will send notifications to the desktop. If the checkbox is unchecked, a modal window with a countdown to continue work will open on top of all windows instead of notifications. Modal window works incorrect on Linux
 pref_work_slider_tooltip =
@@ -69,9 +129,90 @@ pref_break_slider_tooltip =
     Time change by the minute. Press Shift to
     set the time in 10-minute increments
 pref_break_change = Time changes from 1 to 30 minutes
+pref_long_break_slider_tooltip =
+    The change step is 5 minutes. Press Shift
+    to change the time in minute increments
+pref_long_break_change = Time changes from 5 minutes to 1 hour
+pref_long_break_every = Every N cycles:
+pref_warnings = Warnings
+pref_warnings_tooltip =
+    Notifications sent before a break begins, so that
+    it does not interrupt you in the middle of typing
+pref_warning_early = Early
+pref_warning_final = Final
+pref_daily_limit = Daily limit
+pref_daily_limit_tooltip =
+    Total work time per day. You will be warned at 80%
+    and 100% of it. The step is 30 minutes
+pref_daily_limit_lock = Lock the screen on the limit
+pref_day_rollover = The day begins at
+pref_week_start = The week begins on
+monday = Monday
+saturday = Saturday
+sunday = Sunday
+pref_postpone_tooltip =
+    A break can be postponed if it came at a bad
+    time. Each postponement is saved in statistics
+pref_postpone_by = By
+pref_postpone_max = Times per cycle:
+pref_strict = Strict mode
+pref_strict_tooltip =
+    A break cannot be paused, stopped, postponed or ended
+    early. Strict mode cannot be turned off during a break
+pref_strict_mode = Strict breaks
+pref_emergency_phrase = Emergency phrase:
+pref_emergency_phrase_tooltip =
+    Typing this phrase skips a strict break. Each skip is
+    saved in statistics. Leave empty to disable
+pref_escalation = Ignored breaks
+pref_escalation_tooltip =
+    If you keep working during a break, the reminder gets
+    harder to miss. Only the time you are active counts.
+    The level reached is saved in statistics
+pref_escalation_enabled = Escalate reminders
+escalation_notification = Notification
+escalation_critical = Critical notification
+escalation_window = Break window
+escalation_lock = Screen lock
+pref_suggest = Suggested intervals
+pref_suggest_tooltip = The intervals are suggested by your statistics: how often breaks are postponed, cut short or skipped and how long the phases really last
+pref_suggest_intervals = Work { $work }, break { $free }
+pref_suggest_apply = Apply
+pref_suggest_none = The current intervals fit, or there is not enough history yet
+suggest_postponed = The break was postponed { $count } times in the last { $phases } work phases: longer work fits you better
+suggest_work_length = Work phases usually last { $median }
+suggest_breaks_cut = { $count } of the last { $breaks } breaks were cut short or skipped. They usually last { $median }: a shorter break is easier to keep
+pref_auto_adjust = Adjust the intervals every week
+pref_auto_adjust_work = Work
+pref_auto_adjust_break = Break
+pref_interval_policy = When the intervals change
+pref_interval_policy_tooltip = What happens to the running phase when its duration is changed
+policy_apply_now = Apply now
+policy_scale = Apply now, keep the progress
+policy_next_cycle = From the next cycle
+pref_micro_break_tooltip =
+    Short eye breaks on their own schedule, e.g.
+    20 seconds every 20 minutes (the 20-20-20 rule)
+pref_micro_breaks = Eye micro-breaks
+pref_micro_break_overlay = Countdown window
+pref_micro_break_every = Every
+pref_micro_break_for = For
 
 pref_autostart_lbl = Autostart:
 pref_autostart_btn_add = Add
 pref_autostart_btn_rem = Remove
 
 pref_autostart_not_impl = This function not implemented yet (for Windows)
+
+pref_schedule = Working hours
+pref_schedule_tooltip =
+    The timer starts at the beginning of the working hours
+    and stops at their end. The ranges and their work/break
+    times are set in the TimeKeeper.toml file
+pref_schedule_enabled = Follow the schedule
+
+pref_idle = Idle detection
+pref_idle_backend = Source:
+pref_idle_threshold_tooltip = The work timer is paused after this idle time and resumes when you come back
+idle_disabled = Disabled
+idle_command = Command
@@ -3,6 +3,22 @@
 
 work_notification = Ура! Мне сново надо работать!
 break_notification = Пришла пора немного передохнуть.
+long_break_notification = Пора сделать длинный перерыв. Встаньте и пройдитесь
+break_warning_notification = Перерыв через { $mins } мин. Заканчивайте начатое
+break_final_warning_notification = Перерыв через { $secs } секунд
+daily_limit_notification = Вы отработали { $percent }% дневного лимита. Осталось { $mins } мин.
+daily_limit_reached_notification = Дневной лимит работы исчерпан. Пора заканчивать!
+phase_notification = Время для "{ $name }"
+sequence_finished_notification = Последовательность фаз закончилась. Таймер остановлен
+pause_over_notification = Пауза "{ $reason }" закончилась. Таймер продолжает отсчёт
+intervals_adjusted_notification = Интервалы подстроены по статистике: работа { $work }, перерыв { $free }
+break_ignored_notification = Перерыв ещё идёт. Отойдите от компьютера
+micro_break_notification = Посмотрите вдаль { $secs } секунд
+
+# Durations
+time_hours = { $value } ч
+time_mins = { $value } мин
+time_secs = { $value } с
 
 #############
 # MAIN PAGE #
@@ -11,16 +27,42 @@ break_notification = Пришла пора немного передохнуть
 # Labels
 work = Работа
 break = Перерыв
+long_break = Длинный перерыв
+micro_break = Отдых для глаз
+off_hours = Нерабочее время
 
 # Control buttons
 start = Старт
 pause = Пауза
 stop = Стоп
+postpone = Отложить
+break_now = Перерыв сейчас
+break_now_tooltip = Завершить работу и сделать перерыв прямо сейчас (F8)
+end_break = Закончить перерыв
+end_break_tooltip = Завершить перерыв и вернуться к работе (F8)
+extend = +5 мин
+extend_tooltip = Продлить текущую фазу на 5 минут (F9)
+emergency_skip_placeholder = Введите экстренную фразу и нажмите Enter
+pause_reason = Пауза...
+pause_reason_tooltip = Поставить таймер на паузу с указанием причины. Пауза с заданной длиной закончится сама
+pause_meeting = Встреча (30 мин)
+pause_lunch = Обед (1 ч)
+pause_manual = Пока не продолжу
+paused_for = { $reason }: продолжение через { $time }
+profile_custom = Мои настройки
+profile_tooltip = Профиль интервалов. "Мои настройки" задаются на странице настроек
+postpone_tooltip = Отложить перерыв на { $mins } мин. (F7). Осталось: { $left }
 
 # Statistics subpage
 stats_date = Дата:
 stats_type = Тип:
 stats_duration = Длит.:
+stats_profile = Профиль:
+stats_outcome = Итог:
+outcome_finished = Завершена
+outcome_skipped = Закончена раньше
+outcome_stopped = Остановлена
+outcome_postponed = Отложена
 empty_stats = Статистика пуста...
 stats_export = Экспорт в CSV
 stats_file_locate = Файл будет сохранён в ~/TimeKeeper-statistics.csv
@@ -30,10 +72,27 @@ ten_cycles = Показываются последние 10 циклов
 # Footer buttons
 preferences = Настройки
 about = О программе
+dashboard = Графики
 show-stats = Показать статистику
 hide-stats = Скрыть статистику
 
 
+##################
+# DASHBOARD PAGE #
+dashboard_range = Показать:
+dashboard_days = { $days } дней
+chart_days = Работа и перерывы по дням
+chart_hours = Работа по часам суток
+chart_calendar = Календарь работы
+chart_day_tooltip =
+    { $date }
+    Работа: { $work }
+    Перерывы: { $breaks }
+    Циклов: { $cycles }
+chart_hour_tooltip = { $hour }:00-{ $next }:00: { $work }
+chart_calendar_tooltip = { $date }: { $work }
+
+
 ##############
 # ABOUT PAGE #
 ##############
@@ -59,6 +118,7 @@ pref_break_alerts = Оповещ. о перерыве
 pref_notifications = Уведомления
 pref_save = Сохранить
 pref_close = ОК
+pref_off = Выкл
 pref_notifications_tooltip = Если включено, TimeKeeper будет отсылать уведомления на рабочий стол. Иначе вместо уведомлений поверх всех окон будет открываться модальное окно с обратным отсчётом времени до продолжения работы. Модальное окно может некорректно работать в Linux
 pref_work_slider_tooltip =
     Шаг изменения - 10 минут. Зажмите
@@ -68,9 +128,91 @@ pref_break_slider_tooltip =
     Изменение времени поминутно. Зажмите
     Shift, чтобы установить шаг в 10 минут
 pref_break_change = Время изменяется от 1 до 30 минут
+pref_long_break_slider_tooltip =
+    Шаг изменения - 5 минут. Зажмите
+    Shift, чтобы изменять время поминутно
+pref_long_break_change = Время изменяется от 5 минут до 1 часа
+pref_long_break_every = Каждые N циклов:
+pref_warnings = Предупреждения
+pref_warnings_tooltip =
+    Уведомления перед началом перерыва, чтобы он
+    не прерывал вас на полуслове
+pref_warning_early = Раннее
+pref_warning_final = Последнее
+pref_daily_limit = Дневной лимит
+pref_daily_limit_tooltip =
+    Общее время работы за день. Вы получите предупреждение
+    при 80% и 100% от него. Шаг изменения - 30 минут
+pref_daily_limit_lock = Блокировать экран по лимиту
+pref_day_rollover = День начинается в
+pref_week_start = Неделя начинается с
+monday = Понедельник
+saturday = Суббота
+sunday = Воскресенье
+pref_postpone_tooltip =
+    Перерыв можно отложить, если он пришёлся не
+    вовремя. Каждый перенос сохраняется в статистике
+pref_postpone_by = На
+pref_postpone_max = Раз за цикл:
+pref_strict = Строгий режим
+pref_strict_tooltip =
+    Перерыв нельзя поставить на паузу, остановить, отложить
+    или закончить раньше. Во время перерыва режим не выключить
+pref_strict_mode = Строгие перерывы
+pref_emergency_phrase = Экстренная фраза:
+pref_emergency_phrase_tooltip =
+    Ввод этой фразы позволяет пропустить строгий перерыв.
+    Каждый пропуск сохраняется в статистике. Оставьте пустой,
+    чтобы отключить
+pref_escalation = Игнорирование перерывов
+pref_escalation_tooltip =
+    Если продолжать работать во время перерыва, напоминание
+    становится всё настойчивее. Учитывается только время
+    активности. Достигнутый уровень сохраняется в статистике
+pref_escalation_enabled = Усиливать напоминания
+escalation_notification = Уведомление
+escalation_critical = Важное уведомление
+escalation_window = Окно перерыва
+escalation_lock = Блокировка экрана
+pref_suggest = Рекомендуемые интервалы
+pref_suggest_tooltip = Интервалы подобраны по статистике: как часто перерывы откладываются, обрываются или пропускаются и сколько на самом деле длятся фазы
+pref_suggest_intervals = Работа { $work }, перерыв { $free }
+pref_suggest_apply = Применить
+pref_suggest_none = Текущие интервалы подходят, или статистики пока недостаточно
+suggest_postponed = Перерыв откладывали { $count } раз за последние { $phases } рабочих фаз: вам подходит более длинная работа
+suggest_work_length = Рабочие фазы обычно длятся { $median }
+suggest_breaks_cut = { $count } из последних { $breaks } перерывов были оборваны или пропущены. Обычно они длятся { $median }: короткий перерыв легче соблюдать
+pref_auto_adjust = Подстраивать интервалы каждую неделю
+pref_auto_adjust_work = Работа
+pref_auto_adjust_break = Перерыв
+pref_interval_policy = При смене интервалов
+pref_interval_policy_tooltip = Что происходит с текущей фазой, когда меняется её длительность
+policy_apply_now = Применить сразу
+policy_scale = Применить сразу, сохранив прогресс
+policy_next_cycle = Со следующего цикла
+pref_micro_break_tooltip =
+    Короткие перерывы для глаз по своему расписанию,
+    например, 20 секунд каждые 20 минут (правило 20-20-20)
+pref_micro_breaks = Микроперерывы для глаз
+pref_micro_break_overlay = Окно обратного отсчёта
+pref_micro_break_every = Каждые
+pref_micro_break_for = На
 
 pref_autostart_lbl = Автозагрузка:
 pref_autostart_btn_add = Добавить
 pref_autostart_btn_rem = Удалить
 
 pref_autostart_not_impl = Эта функция ещё не сделана (для Windows)
+
+pref_schedule = Рабочее время
+pref_schedule_tooltip =
+    Таймер запускается в начале рабочего времени и
+    останавливается в конце. Диапазоны и их время работы
+    и отдыха задаются в файле TimeKeeper.toml
+pref_schedule_enabled = Следовать расписанию
+
+pref_idle = Бездействие
+pref_idle_backend = Источник:
+pref_idle_threshold_tooltip = Таймер работы ставится на паузу после этого времени бездействия и продолжает отсчёт, когда вы вернётесь
+idle_disabled = Отключено
+idle_command = Команда
@@ -0,0 +1,429 @@
+//! Daily, weekly and monthly summaries of the statistics
+//!
+//! The phases are grouped by the local day they ended in. The day begins at
+//! [`Config::day_rollover_hour`], the week begins on [`Config::week_start`].
+//! The summaries are used by the in-app views and the exports.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    ops::Range,
+};
+
+use chrono::{Datelike, Days, Local, NaiveDate, TimeZone, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    conf::Config,
+    daily_limit::local_day,
+    fl,
+    stats::{ALL_TIME, PostponeEntry, Stats},
+    stats_store::PhaseKind,
+    time::Time,
+    timer::PhaseOutcome,
+};
+
+/// The first day of the week
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Saturday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub const ALL: [Self; 3] = [Self::Monday, Self::Saturday, Self::Sunday];
+
+    pub fn weekday(self) -> Weekday {
+        match self {
+            Self::Monday => Weekday::Mon,
+            Self::Saturday => Weekday::Sat,
+            Self::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+impl Display for WeekStart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Monday => fl!("monday"),
+            Self::Saturday => fl!("saturday"),
+            Self::Sunday => fl!("sunday"),
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Length of the period of a summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    pub const ALL: [Self; 3] = [Self::Day, Self::Week, Self::Month];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    /// The first day of the period `day` belongs to
+    pub fn start(self, day: NaiveDate, week_start: WeekStart) -> NaiveDate {
+        match self {
+            Self::Day => day,
+            Self::Week => {
+                let offset = day.weekday().days_since(week_start.weekday());
+                day - Days::new(offset as u64)
+            }
+            Self::Month => day.with_day(1).unwrap_or(day),
+        }
+    }
+}
+
+/// How the phases are grouped
+#[derive(Debug, Clone, Copy)]
+pub struct AggregateOptions {
+    /// The day begins at this hour (local time)
+    pub rollover_hour: u8,
+    pub week_start: WeekStart,
+    /// A gap between phases at least this long (the timer was stopped) is
+    /// counted as a break
+    pub min_break: Time,
+}
+
+impl AggregateOptions {
+    pub fn new(conf: &Config) -> Self {
+        Self {
+            rollover_hour: conf.day_rollover_hour,
+            week_start: conf.week_start,
+            min_break: conf.free_time,
+        }
+    }
+}
+
+/// Summary of a day, week or month
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Summary {
+    /// Total work time
+    pub work: Time,
+    /// Total break time
+    pub breaks: Time,
+    /// Number of work phases
+    pub work_phases: u32,
+    /// Number of breaks (the postponed ones are not counted)
+    pub break_phases: u32,
+    /// Number of the work/break cycles: the breaks that were taken
+    pub cycles: u32,
+    /// Number of the phases ended early
+    pub skipped: u32,
+    /// How many times the breaks were postponed
+    pub postponed: u32,
+    /// The longest work time without a break
+    pub longest_stretch: Time,
+}
+
+impl Summary {
+    pub fn average_work(&self) -> Time {
+        average(self.work, self.work_phases)
+    }
+
+    pub fn average_break(&self) -> Time {
+        average(self.breaks, self.break_phases)
+    }
+}
+
+fn average(total: Time, count: u32) -> Time {
+    match count {
+        0 => Time::ZERO,
+        _ => Time::from_secs(total.to_secs() / count),
+    }
+}
+
+/// Summaries of the periods in `range` (UNIX Timestamp) by the first days
+/// of the periods. Periods without any phases are missing
+pub fn summarize(
+    stats: &Stats,
+    period: Period,
+    range: Range<u64>,
+    opts: &AggregateOptions,
+) -> BTreeMap<NaiveDate, Summary> {
+    let key =
+        |timestamp: u64| period.start(local_day(timestamp, opts.rollover_hour), opts.week_start);
+    let mut summaries: BTreeMap<NaiveDate, Summary> = BTreeMap::new();
+    // Текущий отрезок работы без перерыва. Он может перейти в следующий
+    // период - тогда засчитывается в том, где закончился
+    let mut stretch = Time::ZERO;
+    let mut last_end = None;
+
+    for entry in stats.phases(range.clone(), &[]) {
+        let summary = summaries.entry(key(entry.end)).or_default();
+
+        let is_gap = last_end.is_some_and(|end: u64| {
+            entry.start.saturating_sub(end) >= opts.min_break.to_secs() as u64
+        });
+        if is_gap {
+            stretch = Time::ZERO;
+        }
+        last_end = Some(entry.end);
+
+        if entry.outcome == PhaseOutcome::Skipped {
+            summary.skipped += 1;
+        }
+        if entry.is_wtime {
+            summary.work += entry.time;
+            summary.work_phases += 1;
+            stretch += entry.time;
+            summary.longest_stretch = summary.longest_stretch.max(stretch);
+        } else if entry.outcome != PhaseOutcome::Postponed {
+            // Отложенный перерыв не был отдыхом: работа продолжилась
+            summary.breaks += entry.time;
+            summary.break_phases += 1;
+            summary.cycles += 1;
+            stretch = Time::ZERO;
+        }
+    }
+
+    // Переносы перерывов сохраняются отдельно, в том числе из первой версии
+    // статистики, где у фаз не было итога
+    for entry in stats.events::<PostponeEntry>(range) {
+        summaries.entry(key(entry.date)).or_default().postponed += 1;
+    }
+
+    summaries
+}
+
+/// Work time in `range` (UNIX Timestamp) by the hours of the day (local
+/// time). A phase is counted back from its end, its pauses are not taken
+/// into account
+pub fn hourly_work(stats: &Stats, range: Range<u64>) -> [Time; 24] {
+    let mut hours = [Time::ZERO; 24];
+    for entry in stats.phases(range, &[PhaseKind::Work]) {
+        let mut moment = entry.end.saturating_sub(entry.time.to_secs() as u64);
+        while moment < entry.end {
+            let Some(dt) = Local.timestamp_opt(moment as i64, 0).earliest() else {
+                break;
+            };
+            let next = (moment + 3600 - (dt.minute() * 60 + dt.second()) as u64).min(entry.end);
+            hours[dt.hour() as usize] += Time::from_secs((next - moment) as u32);
+            moment = next;
+        }
+    }
+
+    hours
+}
+
+/// CSV with the summaries of all days, weeks and months
+pub fn gen_summary_csv(stats: &Stats, opts: &AggregateOptions) -> String {
+    // Запись в String не может завершиться ошибкой, поэтому результат
+    // `writeln!` игнорируется
+    let mut csv = "period;start;work;breaks;cycles;average work;average break;skipped;\
+                   postponed;longest stretch\n"
+        .to_string();
+    for period in Period::ALL {
+        for (start, summary) in summarize(stats, period, ALL_TIME, opts) {
+            let _ = writeln!(
+                csv,
+                "{};{};{};{};{};{};{};{};{};{}",
+                period.name(),
+                start.format("%d.%m.%Y"),
+                summary.work,
+                summary.breaks,
+                summary.cycles,
+                summary.average_work(),
+                summary.average_break(),
+                summary.skipped,
+                summary.postponed,
+                summary.longest_stretch,
+            );
+        }
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::StatisticEntry;
+
+    const OPTS: AggregateOptions = AggregateOptions {
+        rollover_hour: 4,
+        week_start: WeekStart::Monday,
+        min_break: Time::from_mins(15),
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Local date&time in UNIX Timestamp format
+    fn at(day: NaiveDate, hour: u32, min: u32) -> u64 {
+        let dt = day.and_hms_opt(hour, min, 0).unwrap();
+        Local.from_local_datetime(&dt).unwrap().timestamp() as u64
+    }
+
+    /// The phase which ended at `end` and lasted `mins`
+    fn phase(is_wtime: bool, end: u64, mins: u32, outcome: PhaseOutcome) -> StatisticEntry {
+        let time = Time::from_mins(mins);
+        StatisticEntry {
+            start: end - time.to_secs() as u64,
+            end,
+            is_wtime,
+            is_long_break: false,
+            planned: Some(time),
+            time,
+            outcome,
+            phase: String::new(),
+            profile: String::new(),
+            escalation: None,
+        }
+    }
+
+    fn work(end: u64, mins: u32) -> StatisticEntry {
+        phase(true, end, mins, PhaseOutcome::Finished)
+    }
+
+    #[test]
+    fn rollover_hour() {
+        let day = date(2025, 7, 10);
+        let mut stats = Stats::default();
+        stats.push(work(at(day, 3, 59), 30));
+        stats.push(work(at(day, 4, 0), 20));
+        stats.push(work(at(day + Days::new(1), 3, 0), 10));
+
+        let days = summarize(&stats, Period::Day, ALL_TIME, &OPTS);
+        // До 4 утра - ещё предыдущий день
+        assert_eq!(days[&date(2025, 7, 9)].work, Time::from_mins(30));
+        assert_eq!(days[&day].work, Time::from_mins(30));
+        assert_eq!(days[&day].work_phases, 2);
+        assert_eq!(days.len(), 2);
+
+        let opts = AggregateOptions {
+            rollover_hour: 0,
+            ..OPTS
+        };
+        let days = summarize(&stats, Period::Day, ALL_TIME, &opts);
+        assert_eq!(days[&day].work, Time::from_mins(50));
+        assert_eq!(days[&(day + Days::new(1))].work, Time::from_mins(10));
+    }
+
+    #[test]
+    fn week_starts() {
+        // 10 июля 2025 года - четверг
+        let thursday = date(2025, 7, 10);
+        let starts = [
+            (WeekStart::Monday, date(2025, 7, 7)),
+            (WeekStart::Saturday, date(2025, 7, 5)),
+            (WeekStart::Sunday, date(2025, 7, 6)),
+        ];
+        for (week_start, start) in starts {
+            assert_eq!(Period::Week.start(thursday, week_start), start);
+            // Первый день недели - сам себе начало
+            assert_eq!(Period::Week.start(start, week_start), start);
+            let last = start + Days::new(6);
+            assert_eq!(Period::Week.start(last, week_start), start);
+        }
+    }
+
+    #[test]
+    fn month_starts() {
+        assert_eq!(
+            Period::Month.start(date(2025, 7, 31), WeekStart::Monday),
+            date(2025, 7, 1)
+        );
+        assert_eq!(
+            Period::Month.start(date(2024, 2, 29), WeekStart::Monday),
+            date(2024, 2, 1)
+        );
+
+        let mut stats = Stats::default();
+        // Ночь на 1 августа - ещё июль
+        stats.push(work(at(date(2025, 8, 1), 2, 0), 30));
+        stats.push(work(at(date(2025, 8, 1), 9, 0), 20));
+        let months = summarize(&stats, Period::Month, ALL_TIME, &OPTS);
+        assert_eq!(months[&date(2025, 7, 1)].work, Time::from_mins(30));
+        assert_eq!(months[&date(2025, 8, 1)].work, Time::from_mins(20));
+    }
+
+    #[test]
+    fn longest_stretch_and_counts() {
+        let day = date(2025, 7, 10);
+        let mut stats = Stats::default();
+        stats.push(work(at(day, 10, 0), 60));
+        // Перерыв отложен: работа продолжается без отдыха
+        stats.push(phase(false, at(day, 10, 1), 1, PhaseOutcome::Postponed));
+        stats.postponed.push(PostponeEntry {
+            date: at(day, 10, 1),
+            time: Time::from_mins(5),
+        });
+        stats.push(work(at(day, 10, 6), 5));
+        // Таймер был остановлен на 24 минуты - это перерыв
+        stats.push(work(at(day, 11, 0), 30));
+        stats.push(phase(false, at(day, 11, 5), 5, PhaseOutcome::Skipped));
+        stats.push(work(at(day, 12, 5), 60));
+        stats.push(phase(false, at(day, 12, 20), 15, PhaseOutcome::Finished));
+
+        let summary = summarize(&stats, Period::Day, ALL_TIME, &OPTS)[&day];
+        assert_eq!(summary.longest_stretch, Time::from_mins(65));
+        assert_eq!(summary.work, Time::from_mins(155));
+        assert_eq!(summary.work_phases, 4);
+        assert_eq!(summary.breaks, Time::from_mins(20));
+        assert_eq!(summary.break_phases, 2);
+        assert_eq!(summary.cycles, 2);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.postponed, 1);
+        assert_eq!(summary.average_work(), Time::from_secs(155 * 60 / 4));
+    }
+
+    #[test]
+    fn short_gap_keeps_stretch() {
+        let day = date(2025, 7, 10);
+        let mut stats = Stats::default();
+        stats.push(work(at(day, 10, 0), 60));
+        // Остановка на 10 минут - меньше перерыва
+        stats.push(work(at(day, 10, 40), 30));
+
+        let summary = summarize(&stats, Period::Day, ALL_TIME, &OPTS)[&day];
+        assert_eq!(summary.longest_stretch, Time::from_mins(90));
+    }
+
+    #[test]
+    fn hourly_split() {
+        let day = date(2025, 7, 10);
+        let mut stats = Stats::default();
+        stats.push(work(at(day, 10, 20), 40));
+        stats.push(phase(false, at(day, 10, 35), 15, PhaseOutcome::Finished));
+        stats.push(work(at(day, 12, 0), 60));
+
+        let hours = hourly_work(&stats, ALL_TIME);
+        assert_eq!(hours[9], Time::from_mins(20));
+        assert_eq!(hours[10], Time::from_mins(20));
+        assert_eq!(hours[11], Time::from_mins(60));
+        assert_eq!(hours.iter().copied().sum::<Time>(), Time::from_mins(100));
+    }
+
+    #[test]
+    fn summary_csv_lines() {
+        let day = date(2025, 7, 10);
+        let mut stats = Stats::default();
+        stats.push(work(at(day, 10, 0), 30));
+        stats.push(work(at(day + Days::new(1), 10, 0), 20));
+
+        let csv = gen_summary_csv(&stats, &OPTS);
+        let lines = csv.lines().collect::<Vec<_>>();
+        // Заголовок, два дня, одна неделя и один месяц
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("period;start;work"));
+        assert!(lines[1].contains(";10.07.2025;0:30:00;"));
+        assert!(lines[2].contains(";11.07.2025;0:20:00;"));
+        assert!(lines[3].contains(";07.07.2025;0:50:00;"));
+        assert!(lines[4].contains(";01.07.2025;0:50:00;"));
+    }
+}
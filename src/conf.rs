@@ -1,31 +1,252 @@
 //! Configuration file structures
+//!
+//! Durations are [`Time`] values: in the file they are written as a number
+//! of seconds or as a string like `"1h30m"`.
 
-use crate::traits::Toml;
+use anyhow::{Result, anyhow};
+
+use crate::{
+    aggregate::WeekStart,
+    escalation::Escalation,
+    idle::IdleBackend,
+    profile::{self, Profile},
+    schedule::Schedule,
+    sequence::Sequence,
+    suggest::AutoAdjust,
+    time::Time,
+    timer::IntervalPolicy,
+    traits::Toml,
+};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct Config {
-    /// Work time (in seconds)
+    /// Work time
+    ///
+    /// Value change range: [30 min; 3 h]
+    pub work_time: Time,
+
+    /// Free time
+    ///
+    /// Value change range: [1 min; 30 min]
+    pub free_time: Time,
+
+    /// Long break time
     ///
-    /// Value change range: [1; 65535]
-    pub work_time: u16,
+    /// Value change range: [5 min; 1 h]
+    pub long_break_time: Time,
 
-    /// Free time (in seconds)
+    /// A long break follows every `long_break_every` work cycles. `0`
+    /// disables long breaks
     ///
-    /// Value change range: [1; 65535]
-    pub free_time: u16,
+    /// Value change range: [0; 10]
+    pub long_break_every: u8,
+
+    /// What happens to the running phase when the intervals change
+    pub interval_policy: IntervalPolicy,
+
+    /// The early warning is sent this long before a break. Zero disables it
+    ///
+    /// Value change range: [0; 15 min]
+    pub break_warning: Time,
+
+    /// The final warning is sent this long before a break. Zero disables it
+    ///
+    /// Value change range: [0; 2 min]
+    pub break_final_warning: Time,
+
+    /// A break is postponed by this time
+    ///
+    /// Value change range: [1 min; 30 min]
+    pub snooze_time: Time,
+
+    /// How many times a break can be postponed in one cycle. `0` disables
+    /// postponing
+    ///
+    /// Value change range: [0; 5]
+    pub max_snoozes: u8,
+
+    /// Work time per day. Zero disables the limit
+    ///
+    /// Value change range: [0; 12 h]
+    pub daily_limit: Time,
+
+    /// Lock the screen when the daily limit is reached
+    pub daily_limit_lock: bool,
+
+    /// The day begins at this hour (local time), so the work after midnight
+    /// is counted in the previous day
+    ///
+    /// Value change range: [0; 23]
+    pub day_rollover_hour: u8,
+
+    /// The first day of the week in the weekly statistics
+    pub week_start: WeekStart,
+
+    /// Are eye micro-breaks enabled?
+    pub micro_breaks: bool,
+
+    /// Time between eye micro-breaks
+    ///
+    /// Value change range: [5 min; 1 h]
+    pub micro_break_interval: Time,
+
+    /// Eye micro-break time
+    ///
+    /// Value change range: [10 s; 2 min]
+    pub micro_break_time: Time,
+
+    /// Show a small countdown window on top of all windows during an eye
+    /// micro-break
+    pub micro_break_overlay: bool,
 
     /// Will the program send notifications to the desktop?
     pub desktop_notifications: bool,
+
+    /// Strict mode: a break cannot be paused, stopped, postponed or ended
+    /// early, and the break window always opens
+    pub strict_mode: bool,
+
+    /// The phrase the user must type to skip a strict break. An empty
+    /// phrase disables the emergency skip
+    pub emergency_phrase: String,
+
+    /// What happens if the user keeps working during a break
+    pub escalation: Escalation,
+
+    /// Where to get the user idle time from
+    pub idle_backend: IdleBackend,
+
+    /// After this idle time the work timer is paused automatically
+    ///
+    /// Value change range: [1 min; 30 min]
+    pub idle_threshold: Time,
+
+    /// Command for [`IdleBackend::Command`]. It must print the idle time in
+    /// milliseconds
+    pub idle_command: String,
+
+    /// User-defined phases instead of alternating work and breaks
+    pub sequence: Sequence,
+
+    /// Name of the active profile. Empty - the intervals from the settings
+    pub profile: String,
+
+    /// User profiles (in addition to the built-in presets)
+    pub profiles: Vec<Profile>,
+
+    /// Working hours. Outside them the timer is stopped
+    pub schedule: Schedule,
+
+    /// Weekly adjustment of the intervals by the statistics
+    pub auto_adjust: AutoAdjust,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            work_time: 3600, // Час работы
-            free_time: 900,  // 15 минут отдыха
+            work_time: Time::from_mins(60), // Час работы
+            free_time: Time::from_mins(15), // 15 минут отдыха
+            long_break_time: Time::from_mins(30),
+            long_break_every: 0,
+            interval_policy: IntervalPolicy::default(),
+            break_warning: Time::from_mins(5),        // За 5 минут
+            break_final_warning: Time::from_secs(30), // и за 30 секунд до перерыва
+            snooze_time: Time::from_mins(5),
+            max_snoozes: 2,
+            daily_limit: Time::ZERO,
+            daily_limit_lock: false,
+            day_rollover_hour: 4, // Работа до 4 утра - ещё вчерашний день
+            week_start: WeekStart::default(),
+            micro_breaks: false,
+            micro_break_interval: Time::from_mins(20), // Правило 20-20-20: каждые 20 минут
+            micro_break_time: Time::from_secs(20),     // смотреть 20 секунд вдаль
+            micro_break_overlay: false,
             desktop_notifications: true,
+            strict_mode: false,
+            emergency_phrase: String::new(),
+            escalation: Escalation::default(),
+            idle_backend: IdleBackend::default(),
+            idle_threshold: Time::from_mins(5), // 5 минут бездействия
+            idle_command: "xprintidle".to_string(),
+            sequence: Sequence::default(),
+            profile: String::new(),
+            profiles: Vec::new(),
+            schedule: Schedule::default(),
+            auto_adjust: AutoAdjust::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Checks that the phases last some time. A zero-length phase ends at
+    /// once, and a cycle of such phases never ends. The profiles and the
+    /// working hours are checked too
+    pub fn validate(&self) -> Result<()> {
+        self.validate_intervals()?;
+        for profile in &self.profiles {
+            profile.validate()?;
+        }
+        for range in &self.schedule.ranges {
+            let times = [
+                ("work_time", range.work_time),
+                ("free_time", range.free_time),
+            ];
+            if let Some((name, _)) = times
+                .iter()
+                .find(|(_, time)| time.is_some_and(|time| time.is_zero()))
+            {
+                return Err(anyhow!(
+                    "Working hours '{range}': '{name}' must not be zero"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the intervals of the timer only (see [`Config::validate`]).
+    /// The long break and the postponing may be zero if they are disabled
+    pub fn validate_intervals(&self) -> Result<()> {
+        let times = [
+            ("work_time", self.work_time, true),
+            ("free_time", self.free_time, true),
+            (
+                "long_break_time",
+                self.long_break_time,
+                self.long_break_every > 0,
+            ),
+            ("snooze_time", self.snooze_time, self.max_snoozes > 0),
+        ];
+        if let Some((name, ..)) = times
+            .iter()
+            .find(|(_, time, is_used)| *is_used && time.is_zero())
+        {
+            return Err(anyhow!("'{name}' must not be zero"));
+        }
+        self.sequence.validate()
+    }
+
+    /// Profiles for the picker: the intervals from the settings, the user
+    /// profiles and the built-in presets
+    pub fn all_profiles(&self) -> Vec<Profile> {
+        let mut profiles = vec![Profile::custom(self)];
+        profiles.extend(self.profiles.iter().cloned());
+        profiles.extend(profile::presets());
+        profiles
+    }
+
+    /// Finds a user profile or a preset by its name. User profiles take
+    /// precedence over the presets with the same name
+    pub fn find_profile(&self, name: &str) -> Option<Profile> {
+        if name.is_empty() {
+            return None;
         }
+        self.profiles
+            .iter()
+            .cloned()
+            .chain(profile::presets())
+            .find(|profile| profile.name == name)
     }
 }
 
@@ -33,3 +254,84 @@ impl Default for Config {
 // we are completely satisfied with them, so there is no need to implement
 // them again. Let's leave the empty brackets.
 impl Toml for Config {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        schedule::{ClockTime, Weekday, WorkRange},
+        sequence::Phase,
+    };
+
+    #[test]
+    fn default_is_valid() {
+        assert!(Config::default().validate().is_ok());
+        for profile in profile::presets() {
+            assert!(profile.validate().is_ok(), "{}", profile.name);
+        }
+    }
+
+    #[test]
+    fn zero_intervals() {
+        let conf = Config {
+            free_time: Time::ZERO,
+            ..Default::default()
+        };
+        assert!(conf.validate().is_err());
+
+        // Отключённые длинный перерыв и перенос могут быть нулевыми
+        let conf = Config {
+            long_break_time: Time::ZERO,
+            long_break_every: 0,
+            snooze_time: Time::ZERO,
+            max_snoozes: 0,
+            ..Default::default()
+        };
+        assert!(conf.validate().is_ok());
+
+        let conf = Config {
+            long_break_time: Time::ZERO,
+            long_break_every: 4,
+            ..Default::default()
+        };
+        assert!(conf.validate().is_err());
+    }
+
+    #[test]
+    fn zero_phase_in_sequence() {
+        let phase = |time| Phase {
+            name: "Work".to_string(),
+            is_work: true,
+            time,
+            color: None,
+            notification: None,
+        };
+        let mut conf = Config::default();
+        conf.sequence.phases = vec![phase(Time::from_mins(50)), phase(Time::ZERO)];
+        assert!(conf.validate().is_err());
+
+        // То же в профиле
+        let mut profile = Profile::custom(&Config::default());
+        profile.name = "Broken".to_string();
+        profile.sequence = conf.sequence.clone();
+        let conf = Config {
+            profiles: vec![profile],
+            ..Default::default()
+        };
+        assert!(conf.validate().is_err());
+    }
+
+    #[test]
+    fn zero_schedule_override() {
+        let mut conf = Config::default();
+        conf.schedule.ranges.push(WorkRange {
+            days: vec![Weekday::Mon],
+            start: ClockTime { hour: 9, min: 0 },
+            end: ClockTime { hour: 18, min: 0 },
+            profile: None,
+            work_time: None,
+            free_time: Some(Time::ZERO),
+        });
+        assert!(conf.validate().is_err());
+    }
+}
@@ -1,5 +1,9 @@
 //! Constants and global variables
 
+use std::{ops::RangeInclusive, time::Duration};
+
+use crate::time::Time;
+
 pub const PROG_NAME: &str = "TimeKeeper";
 pub const PROG_VER: &str = env!("CARGO_PKG_VERSION");
 pub const PROG_LOGO: &[u8] = include_bytes!("../assets/logo1.png");
@@ -23,6 +27,51 @@ pub const PROG_PREFERENCES: &str = "TimeKeeper.toml";
 /// каждый такой цикл)
 pub const PROG_STATISTICS: &str = "stat.toml";
 
+/// База данных со всей историей статистики
+pub const PROG_STATISTICS_DB: &str = "stat.db";
+
+/// Saved state of the running timer
+pub const PROG_TIMER_STATE: &str = "timer_state.toml";
+
+/// The timer state is saved at least this often (in seconds)
+pub const TIMER_STATE_SAVE_INTERVAL: u64 = 30;
+
+/// The current phase is made longer by this time with the "+5 min" button
+pub const PHASE_EXTEND_TIME: Time = Time::from_mins(5);
+
+/// The user is warned when the work time of the day reaches these
+/// percentages of the daily limit
+pub const DAILY_LIMIT_THRESHOLDS: [u8; 2] = [80, 100];
+
+/// Intervals are suggested only after this many work phases or breaks
+pub const SUGGEST_MIN_PHASES: usize = 3;
+
+/// Work time set on the Preferences page or suggested stays within this
+/// range (in seconds)
+pub const SUGGEST_WORK_RANGE: RangeInclusive<u32> = 1800..=10800;
+
+/// Free time set on the Preferences page or suggested stays within this
+/// range (in seconds)
+pub const SUGGEST_BREAK_RANGE: RangeInclusive<u32> = 60..=1800;
+
+/// The intervals are adjusted automatically at most this often (in seconds)
+pub const AUTO_ADJUST_PERIOD: u64 = 7 * 24 * 3600;
+
+/// Intervals are suggested by the phases of this last period (in seconds).
+/// Each weekly adjustment learns from the week since the previous one
+pub const SUGGEST_PERIOD: u64 = AUTO_ADJUST_PERIOD;
+
+/// The user counts as active (ignoring a break) if the idle time is shorter
+/// than this
+pub const ESCALATION_ACTIVE_IDLE: Duration = Duration::from_secs(10);
+
+/// Size of the main window
+pub const MAIN_WINDOW_SIZE: (f32, f32) = (300., 315.);
+
+/// The main window is enlarged to this size on the dashboard page, the
+/// charts don't fit into the usual one
+pub const DASHBOARD_WINDOW_SIZE: (f32, f32) = (640., 480.);
+
 /// Path to the autostart directory (Unix)
 pub const PROG_AUTOSTART_DIR: &str = ".local/share/autostart/";
 
@@ -0,0 +1,212 @@
+//! Daily work limit
+//!
+//! The work time of the day is the sum of today's work phases from the
+//! statistics plus the running phase. When it crosses one of the
+//! [`DAILY_LIMIT_THRESHOLDS`] the user is warned, each threshold once a day.
+
+use chrono::{DateTime, Days, Local, NaiveDate, TimeDelta, TimeZone};
+
+use crate::{conf::Config, consts::DAILY_LIMIT_THRESHOLDS, time::Time};
+
+#[derive(Debug, Clone)]
+pub struct DailyLimit {
+    /// Work time per day. Zero disables the limit
+    limit: Time,
+
+    /// Beginning of the day the warnings were counted for (UNIX Timestamp)
+    day_start: u64,
+
+    /// How many thresholds have already been crossed today
+    crossed: usize,
+}
+
+impl DailyLimit {
+    pub fn new(conf: &Config) -> Self {
+        Self {
+            limit: conf.daily_limit,
+            day_start: 0,
+            crossed: 0,
+        }
+    }
+
+    pub fn set_limit(&mut self, conf: &Config) {
+        self.limit = conf.daily_limit;
+    }
+
+    pub fn limit(&self) -> Time {
+        self.limit
+    }
+
+    /// Checks the work time of the day beginning at `day_start`. Returns the
+    /// percentage of the limit if a new threshold was crossed. If several
+    /// thresholds were crossed at once, only the highest one is returned
+    pub fn check(&mut self, day_start: u64, worked: Time) -> Option<u8> {
+        if day_start != self.day_start {
+            self.day_start = day_start;
+            self.crossed = 0;
+        }
+        if self.limit.is_zero() {
+            return None;
+        }
+
+        let crossed = DAILY_LIMIT_THRESHOLDS
+            .iter()
+            .take_while(|&&percent| {
+                worked.to_secs() as u64 * 100 >= self.limit.to_secs() as u64 * percent as u64
+            })
+            .count();
+        if crossed <= self.crossed {
+            return None;
+        }
+
+        self.crossed = crossed;
+        Some(DAILY_LIMIT_THRESHOLDS[crossed - 1])
+    }
+}
+
+/// Beginning of the day `now` belongs to (UNIX Timestamp). The day begins at
+/// `rollover_hour` local time, so the work after midnight can be counted in
+/// the previous day
+pub fn day_start(now: DateTime<Local>, rollover_hour: u8) -> u64 {
+    let mut start = now
+        .date_naive()
+        .and_hms_opt(rollover_hour.min(23) as u32, 0, 0)
+        .unwrap_or_default();
+    if now.naive_local() < start {
+        start = start - Days::new(1);
+    }
+
+    // Во время перевода часов вперёд этого времени может не быть - тогда
+    // сутки начинаются часом позже
+    Local
+        .from_local_datetime(&start)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(start + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .unwrap_or(now)
+        .timestamp()
+        .max(0) as u64
+}
+
+/// The local day the moment `timestamp` (UNIX Timestamp) belongs to. The day
+/// begins at `rollover_hour` as in [`day_start`]
+pub fn local_day(timestamp: u64, rollover_hour: u8) -> NaiveDate {
+    let dt = Local
+        .timestamp_opt(timestamp as i64, 0)
+        .earliest()
+        .unwrap_or_default();
+    (dt.naive_local() - TimeDelta::hours(rollover_hour.min(23) as i64)).date()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Local date&time in July 2025
+    fn at(day: u32, hour: u32, min: u32) -> DateTime<Local> {
+        let dt = NaiveDate::from_ymd_opt(2025, 7, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap();
+        Local.from_local_datetime(&dt).unwrap()
+    }
+
+    fn limit(hours: u32) -> DailyLimit {
+        DailyLimit::new(&Config {
+            daily_limit: Time::from_mins(hours * 60),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn day_starts_at_rollover_hour() {
+        let start = at(10, 4, 0).timestamp() as u64;
+        // До 4 утра - ещё предыдущий день
+        assert_eq!(day_start(at(10, 3, 59), 4), at(9, 4, 0).timestamp() as u64);
+        assert_eq!(day_start(at(10, 4, 0), 4), start);
+        assert_eq!(day_start(at(10, 23, 59), 4), start);
+        assert_eq!(day_start(at(11, 3, 59), 4), start);
+        assert_eq!(day_start(at(10, 0, 30), 0), at(10, 0, 0).timestamp() as u64);
+    }
+
+    #[test]
+    fn local_day_at_rollover_hour() {
+        let day = NaiveDate::from_ymd_opt(2025, 7, 10).unwrap();
+        let ts = |dt: DateTime<Local>| dt.timestamp() as u64;
+        assert_eq!(local_day(ts(at(10, 3, 59)), 4), day.pred_opt().unwrap());
+        assert_eq!(local_day(ts(at(10, 4, 0)), 4), day);
+        assert_eq!(local_day(ts(at(11, 3, 59)), 4), day);
+        assert_eq!(local_day(ts(at(10, 0, 0)), 0), day);
+
+        // Оба способа считают один и тот же день
+        for hour in [0, 3, 4, 12, 23] {
+            let now = at(10, hour, 30);
+            let start = day_start(now, 4);
+            assert_eq!(local_day(start, 4), local_day(ts(now), 4));
+        }
+    }
+
+    #[test]
+    fn thresholds_once_per_day() {
+        let mut limit = limit(8);
+        let today = day_start(at(10, 12, 0), 4);
+        let tomorrow = day_start(at(11, 12, 0), 4);
+
+        assert_eq!(limit.check(today, Time::from_mins(383)), None);
+        // 80% от 8 часов - 6 ч 24 мин
+        assert_eq!(limit.check(today, Time::from_mins(384)), Some(80));
+        assert_eq!(limit.check(today, Time::from_mins(400)), None);
+        assert_eq!(limit.check(today, Time::from_mins(480)), Some(100));
+        assert_eq!(limit.check(today, Time::from_mins(480)), None);
+        assert_eq!(limit.check(today, Time::from_mins(600)), None);
+
+        // Новый день - предупреждения снова
+        assert_eq!(limit.check(tomorrow, Time::from_mins(10)), None);
+        assert_eq!(limit.check(tomorrow, Time::from_mins(390)), Some(80));
+    }
+
+    #[test]
+    fn highest_crossed_threshold() {
+        let mut limit = limit(1);
+        let today = day_start(at(10, 12, 0), 4);
+
+        // Сразу оба порога (например, после восстановления состояния)
+        assert_eq!(limit.check(today, Time::from_mins(70)), Some(100));
+        assert_eq!(limit.check(today, Time::from_mins(80)), None);
+    }
+
+    #[test]
+    fn work_around_rollover() {
+        let mut limit = limit(1);
+        // Работа в 3:30 пятницы - это ещё четверг
+        let night = day_start(at(11, 3, 30), 4);
+        let morning = day_start(at(11, 4, 30), 4);
+        assert_eq!(night, day_start(at(10, 12, 0), 4));
+
+        assert_eq!(limit.check(night, Time::from_mins(60)), Some(100));
+        assert_eq!(limit.check(night, Time::from_mins(65)), None);
+        // В 4:30 начался новый день, счёт начинается заново
+        assert_eq!(limit.check(morning, Time::from_mins(5)), None);
+        assert_eq!(limit.check(morning, Time::from_mins(60)), Some(100));
+    }
+
+    #[test]
+    fn zero_limit_disabled() {
+        let mut limit = limit(0);
+        let today = day_start(at(10, 12, 0), 4);
+
+        assert_eq!(limit.limit(), Time::ZERO);
+        assert_eq!(limit.check(today, Time::ZERO), None);
+        assert_eq!(limit.check(today, Time::from_mins(24 * 60)), None);
+
+        // Включённый позже лимит учитывает работу за день
+        limit.set_limit(&Config {
+            daily_limit: Time::from_mins(60),
+            ..Default::default()
+        });
+        assert_eq!(limit.check(today, Time::from_mins(24 * 60)), Some(100));
+    }
+}
@@ -0,0 +1,306 @@
+//! Escalation when a break is ignored
+//!
+//! A break begins with a normal notification. If the user keeps working, the
+//! reminder gets harder to miss step by step: a critical notification, the
+//! always-on-top break window and, at last, a locked screen. The time is
+//! counted only while the user is active (according to the idle source);
+//! without an idle source the whole break counts.
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{consts::ESCALATION_ACTIVE_IDLE, fl, time::Time};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Escalation {
+    /// Is the escalation used?
+    pub enabled: bool,
+
+    /// A critical notification is sent after this much ignored time. Zero
+    /// disables the step
+    pub critical_after: Time,
+
+    /// The break window is opened after this much ignored time. Zero
+    /// disables the step
+    pub window_after: Time,
+
+    /// The screen is locked after this much ignored time. Zero disables the
+    /// step
+    pub lock_after: Time,
+}
+
+impl Default for Escalation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            critical_after: Time::from_mins(1),
+            window_after: Time::from_mins(2),
+            lock_after: Time::ZERO, // Блокировка экрана - только по желанию
+        }
+    }
+}
+
+impl Escalation {
+    /// Ignored time after which `level` is reached. `None` if the step is
+    /// disabled
+    fn after(&self, level: EscalationLevel) -> Option<Time> {
+        let after = match level {
+            EscalationLevel::Notification => return Some(Time::ZERO),
+            EscalationLevel::Critical => self.critical_after,
+            EscalationLevel::Window => self.window_after,
+            EscalationLevel::Lock => self.lock_after,
+        };
+        (!after.is_zero()).then_some(after)
+    }
+}
+
+/// Steps of the escalation. The level reached is saved with the break in the
+/// statistics
+#[derive(
+    Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum EscalationLevel {
+    /// Only the usual notification about the break
+    #[default]
+    Notification,
+    Critical,
+    Window,
+    Lock,
+}
+
+impl EscalationLevel {
+    pub const ALL: [Self; 4] = [Self::Notification, Self::Critical, Self::Window, Self::Lock];
+}
+
+impl Display for EscalationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Notification => fl!("escalation_notification"),
+            Self::Critical => fl!("escalation_critical"),
+            Self::Window => fl!("escalation_window"),
+            Self::Lock => fl!("escalation_lock"),
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Counts how long the user ignores the current break
+#[derive(Debug, Default, Clone)]
+pub struct Escalator {
+    /// The level reached in the current break. `None` if there is no break
+    level: Option<EscalationLevel>,
+
+    /// Time the user was active during the break
+    ignored: Duration,
+
+    /// The moment of the last check. `None` while the timer is paused
+    last_check: Option<Instant>,
+
+    /// The last idle time reported by the idle source. `None` - there is no
+    /// idle source
+    idle: Option<Duration>,
+}
+
+impl Escalator {
+    /// The break has begun
+    pub fn start(&mut self, now: Instant) {
+        self.level = Some(EscalationLevel::Notification);
+        self.ignored = Duration::ZERO;
+        self.last_check = Some(now);
+    }
+
+    /// The break is over. Returns the level reached
+    pub fn finish(&mut self) -> Option<EscalationLevel> {
+        self.last_check = None;
+        self.level.take()
+    }
+
+    pub fn level(&self) -> Option<EscalationLevel> {
+        self.level
+    }
+
+    /// The timer was paused: the pause is not counted
+    pub fn suspend(&mut self) {
+        self.last_check = None;
+    }
+
+    pub fn set_idle(&mut self, idle: Option<Duration>) {
+        self.idle = idle;
+    }
+
+    fn is_active(&self) -> bool {
+        self.idle.is_none_or(|idle| idle < ESCALATION_ACTIVE_IDLE)
+    }
+
+    /// Counts the time since the last check if the user is active. Returns
+    /// the new level if it has been reached just now
+    pub fn check(&mut self, conf: &Escalation, now: Instant) -> Option<EscalationLevel> {
+        let level = self.level?;
+        if let Some(last) = self.last_check.replace(now)
+            && self.is_active()
+        {
+            self.ignored += now.saturating_duration_since(last);
+        }
+        if !conf.enabled {
+            return None;
+        }
+
+        let ignored = Time::from_duration(self.ignored);
+        let reached = EscalationLevel::ALL
+            .into_iter()
+            .filter(|&step| conf.after(step).is_some_and(|after| ignored >= after))
+            .max()?;
+        if reached <= level {
+            return None;
+        }
+
+        self.level = Some(reached);
+        Some(reached)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Critical notification after 1 min, window after 2 min, lock after
+    /// 3 min
+    fn conf() -> Escalation {
+        Escalation {
+            enabled: true,
+            critical_after: Time::from_mins(1),
+            window_after: Time::from_mins(2),
+            lock_after: Time::from_mins(3),
+        }
+    }
+
+    fn secs(start: Instant, secs: u64) -> Instant {
+        start + Duration::from_secs(secs)
+    }
+
+    /// Checks the escalator every 10 seconds from `from` to `to` and
+    /// returns the levels reached with the moments (seconds)
+    fn run(
+        escalator: &mut Escalator,
+        conf: &Escalation,
+        t0: Instant,
+        from: u64,
+        to: u64,
+    ) -> Vec<(u64, EscalationLevel)> {
+        (from..=to)
+            .step_by(10)
+            .filter_map(|s| escalator.check(conf, secs(t0, s)).map(|level| (s, level)))
+            .collect()
+    }
+
+    #[test]
+    fn steps_in_order() {
+        let t0 = Instant::now();
+        let mut escalator = Escalator::default();
+        assert_eq!(escalator.level(), None);
+        assert_eq!(escalator.check(&conf(), t0), None);
+
+        escalator.start(t0);
+        assert_eq!(escalator.level(), Some(EscalationLevel::Notification));
+        assert_eq!(
+            run(&mut escalator, &conf(), t0, 0, 300),
+            [
+                (60, EscalationLevel::Critical),
+                (120, EscalationLevel::Window),
+                (180, EscalationLevel::Lock),
+            ]
+        );
+        assert_eq!(escalator.finish(), Some(EscalationLevel::Lock));
+        assert_eq!(escalator.level(), None);
+        assert_eq!(escalator.check(&conf(), secs(t0, 400)), None);
+    }
+
+    #[test]
+    fn disabled_steps_skipped() {
+        let t0 = Instant::now();
+        let conf = Escalation {
+            critical_after: Time::ZERO,
+            lock_after: Time::ZERO,
+            ..conf()
+        };
+        let mut escalator = Escalator::default();
+
+        escalator.start(t0);
+        assert_eq!(
+            run(&mut escalator, &conf, t0, 0, 600),
+            [(120, EscalationLevel::Window)]
+        );
+        assert_eq!(escalator.finish(), Some(EscalationLevel::Window));
+    }
+
+    #[test]
+    fn late_check_reaches_highest_step() {
+        let t0 = Instant::now();
+        let mut escalator = Escalator::default();
+
+        escalator.start(t0);
+        assert_eq!(
+            escalator.check(&conf(), secs(t0, 150)),
+            Some(EscalationLevel::Window)
+        );
+        assert_eq!(escalator.check(&conf(), secs(t0, 160)), None);
+    }
+
+    #[test]
+    fn disabled_escalation() {
+        let t0 = Instant::now();
+        let conf = Escalation {
+            enabled: false,
+            ..conf()
+        };
+        let mut escalator = Escalator::default();
+
+        escalator.start(t0);
+        assert!(run(&mut escalator, &conf, t0, 0, 600).is_empty());
+        assert_eq!(escalator.finish(), Some(EscalationLevel::Notification));
+    }
+
+    #[test]
+    fn idle_time_not_counted() {
+        let t0 = Instant::now();
+        let mut escalator = Escalator::default();
+
+        escalator.start(t0);
+        escalator.set_idle(Some(Duration::ZERO));
+        assert_eq!(run(&mut escalator, &conf(), t0, 0, 50), []);
+        // Пользователь отошёл от компьютера - перерыв не игнорируется
+        escalator.set_idle(Some(ESCALATION_ACTIVE_IDLE));
+        assert_eq!(run(&mut escalator, &conf(), t0, 60, 600), []);
+
+        escalator.set_idle(Some(Duration::from_secs(1)));
+        assert_eq!(
+            run(&mut escalator, &conf(), t0, 610, 700),
+            [
+                (610, EscalationLevel::Critical),
+                (670, EscalationLevel::Window)
+            ]
+        );
+    }
+
+    #[test]
+    fn pause_not_counted() {
+        let t0 = Instant::now();
+        let mut escalator = Escalator::default();
+
+        escalator.start(t0);
+        assert_eq!(escalator.check(&conf(), secs(t0, 30)), None);
+        escalator.suspend();
+        // Первая проверка после паузы только запоминает момент
+        assert_eq!(escalator.check(&conf(), secs(t0, 600)), None);
+        assert_eq!(escalator.check(&conf(), secs(t0, 620)), None);
+        assert_eq!(
+            escalator.check(&conf(), secs(t0, 630)),
+            Some(EscalationLevel::Critical)
+        );
+    }
+}
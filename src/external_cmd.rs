@@ -28,3 +28,20 @@ pub fn open_url(url: &str) -> Result<()> {
     let _ = Command::new(OPEN_CMD).arg(url).status()?;
     Ok(())
 }
+
+/// Locks the user session
+#[cfg(windows)]
+pub fn lock_screen() -> Result<()> {
+    let _ = Command::new("rundll32.exe")
+        .arg("user32.dll,LockWorkStation")
+        .creation_flags(0x08000000)
+        .status()?;
+    Ok(())
+}
+
+/// Locks the user session
+#[cfg(unix)]
+pub fn lock_screen() -> Result<()> {
+    let _ = Command::new("loginctl").arg("lock-session").status()?;
+    Ok(())
+}
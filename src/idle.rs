@@ -0,0 +1,348 @@
+//! User idle detection
+//!
+//! TimeKeeper asks an [`IdleSource`] how long the user has not touched the
+//! keyboard and mouse. The source is selected in the program settings
+//! ([`IdleBackend`]); new backends only need to implement [`IdleSource`].
+
+use std::{
+    fmt::Display,
+    process::Command,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Result, anyhow};
+use iced::futures::{SinkExt, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::fl;
+
+/// How often the idle source is polled
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Source of the user idle time
+pub trait IdleSource: Send {
+    /// How long the user has been idle
+    fn idle_time(&mut self) -> Result<Duration>;
+}
+
+/// Idle detection backends available in the settings
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdleBackend {
+    /// Idle detection is disabled
+    #[default]
+    Disabled,
+    /// `IdleHint` of the current logind session
+    Logind,
+    /// `GetSessionIdleTime` of `org.freedesktop.ScreenSaver`
+    ScreenSaver,
+    /// External command which prints the idle time in milliseconds (e.g.
+    /// `xprintidle`)
+    Command,
+}
+
+impl IdleBackend {
+    pub const ALL: [Self; 4] = [
+        Self::Disabled,
+        Self::Logind,
+        Self::ScreenSaver,
+        Self::Command,
+    ];
+}
+
+impl Display for IdleBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Disabled => fl!("idle_disabled"),
+            Self::Logind => "logind".to_string(),
+            Self::ScreenSaver => "ScreenSaver".to_string(),
+            Self::Command => fl!("idle_command"),
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// What the work timer should do about the user idle time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdleEvent {
+    /// The user has been idle for the threshold: the work timer is paused
+    Away,
+    /// The user is back after being idle for the given time: the work timer
+    /// resumes
+    Back(Duration),
+}
+
+/// Pauses the work timer after the idle threshold and resumes it when the
+/// user is back
+#[derive(Debug, Default, Clone)]
+pub struct IdleTracker {
+    /// The moment the user became idle, if the work timer was paused
+    /// automatically because of it
+    since: Option<SystemTime>,
+}
+
+impl IdleTracker {
+    /// The user controls the timer (or it was stopped): the automatic pause
+    /// no longer applies
+    pub fn reset(&mut self) {
+        self.since = None;
+    }
+
+    /// Compares the idle time with `threshold`. The timer is paused only if
+    /// it counts work time (`is_working`)
+    pub fn check(
+        &mut self,
+        idle: Duration,
+        threshold: Duration,
+        is_working: bool,
+        now: SystemTime,
+    ) -> Option<IdleEvent> {
+        match self.since {
+            None if idle >= threshold && is_working => {
+                self.since = Some(now.checked_sub(idle).unwrap_or(now));
+                Some(IdleEvent::Away)
+            }
+            Some(since) if idle < threshold => {
+                self.since = None;
+                Some(IdleEvent::Back(
+                    now.duration_since(since).unwrap_or_default(),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Runs an external command and reads the idle time (in milliseconds) from
+/// its output
+pub struct CommandIdle {
+    cmd: String,
+}
+
+impl CommandIdle {
+    pub fn new<S: ToString>(cmd: S) -> Self {
+        Self {
+            cmd: cmd.to_string(),
+        }
+    }
+}
+
+impl IdleSource for CommandIdle {
+    fn idle_time(&mut self) -> Result<Duration> {
+        let output = Command::new(&self.cmd).output()?;
+        if !output.status.success() {
+            return Err(anyhow!("'{}' exited with {}", self.cmd, output.status));
+        }
+
+        let ms = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<u64>()?;
+        Ok(Duration::from_millis(ms))
+    }
+}
+
+/// Reads `IdleHint`/`IdleSinceHint` of the current logind session
+#[cfg(target_os = "linux")]
+pub struct LogindIdle {
+    session: crate::logind::SessionProxyBlocking<'static>,
+}
+
+#[cfg(target_os = "linux")]
+impl LogindIdle {
+    pub fn new() -> Result<Self> {
+        let conn = zbus::blocking::Connection::system()?;
+        Ok(Self {
+            session: crate::logind::session_blocking(&conn)?,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IdleSource for LogindIdle {
+    fn idle_time(&mut self) -> Result<Duration> {
+        if !self.session.idle_hint()? {
+            return Ok(Duration::ZERO);
+        }
+
+        // IdleSinceHint - время в микросекундах с начала эпохи UNIX
+        let since = Duration::from_micros(self.session.idle_since_hint()?);
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+
+        Ok(now.saturating_sub(since))
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[zbus::proxy(
+    interface = "org.freedesktop.ScreenSaver",
+    default_service = "org.freedesktop.ScreenSaver",
+    default_path = "/org/freedesktop/ScreenSaver"
+)]
+trait ScreenSaver {
+    fn get_session_idle_time(&self) -> zbus::Result<u32>;
+}
+
+/// Asks the desktop environment through `org.freedesktop.ScreenSaver`
+#[cfg(target_os = "linux")]
+pub struct ScreenSaverIdle {
+    proxy: ScreenSaverProxyBlocking<'static>,
+}
+
+#[cfg(target_os = "linux")]
+impl ScreenSaverIdle {
+    pub fn new() -> Result<Self> {
+        let conn = zbus::blocking::Connection::session()?;
+        Ok(Self {
+            proxy: ScreenSaverProxyBlocking::new(&conn)?,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IdleSource for ScreenSaverIdle {
+    fn idle_time(&mut self) -> Result<Duration> {
+        let secs = self.proxy.get_session_idle_time()?;
+        Ok(Duration::from_secs(secs as u64))
+    }
+}
+
+/// Creates the idle source for the selected backend. Returns `None` if idle
+/// detection is disabled
+pub fn source(backend: IdleBackend, cmd: &str) -> Result<Option<Box<dyn IdleSource>>> {
+    let source: Box<dyn IdleSource> = match backend {
+        IdleBackend::Disabled => return Ok(None),
+        IdleBackend::Command => Box::new(CommandIdle::new(cmd)),
+        #[cfg(target_os = "linux")]
+        IdleBackend::Logind => Box::new(LogindIdle::new()?),
+        #[cfg(target_os = "linux")]
+        IdleBackend::ScreenSaver => Box::new(ScreenSaverIdle::new()?),
+        #[cfg(not(target_os = "linux"))]
+        IdleBackend::Logind | IdleBackend::ScreenSaver => {
+            return Err(anyhow!(
+                "Idle backend '{backend}' is supported only on Linux"
+            ));
+        }
+    };
+
+    Ok(Some(source))
+}
+
+/// Periodically polls the idle source and sends the idle time. Errors are
+/// printed to `stderr` and end the stream
+pub fn watch(backend: IdleBackend, cmd: String) -> impl Stream<Item = Duration> {
+    iced::stream::channel(1, move |mut output| async move {
+        let res: Result<()> = async {
+            let mut source =
+                match tokio::task::spawn_blocking(move || source(backend, &cmd)).await?? {
+                    Some(source) => source,
+                    None => return Ok(()),
+                };
+
+            loop {
+                tokio::time::sleep(POLL_INTERVAL).await;
+
+                // Источники могут блокировать поток (D-Bus, внешняя команда),
+                // поэтому опрашиваем их вне асинхронного рантайма
+                let (src, idle) = tokio::task::spawn_blocking(move || {
+                    let idle = source.idle_time();
+                    (source, idle)
+                })
+                .await?;
+                source = src;
+
+                output.send(idle?).await?;
+            }
+        }
+        .await;
+
+        if let Err(err) = res {
+            eprintln!("Failed to get the user idle time:\n{err}");
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(300);
+
+    /// Idle source which returns a scripted sequence of idle times
+    struct FakeIdle(VecDeque<Duration>);
+
+    impl FakeIdle {
+        fn new(secs: &[u64]) -> Self {
+            Self(secs.iter().map(|&secs| Duration::from_secs(secs)).collect())
+        }
+    }
+
+    impl IdleSource for FakeIdle {
+        fn idle_time(&mut self) -> Result<Duration> {
+            self.0
+                .pop_front()
+                .ok_or_else(|| anyhow!("the script is over"))
+        }
+    }
+
+    /// Polls `source` every [`POLL_INTERVAL`] until the script is over
+    fn run(
+        tracker: &mut IdleTracker,
+        source: &mut dyn IdleSource,
+        is_working: bool,
+    ) -> Vec<Option<IdleEvent>> {
+        let mut now = SystemTime::UNIX_EPOCH + Duration::from_secs(3600);
+        let mut events = Vec::new();
+        while let Ok(idle) = source.idle_time() {
+            now += POLL_INTERVAL;
+            events.push(tracker.check(idle, THRESHOLD, is_working, now));
+        }
+        events
+    }
+
+    #[test]
+    fn pause_after_threshold_and_resume() {
+        let mut tracker = IdleTracker::default();
+        let mut source = FakeIdle::new(&[0, 295, 300, 305, 2]);
+
+        assert_eq!(
+            run(&mut tracker, &mut source, true),
+            vec![
+                None,
+                None,
+                Some(IdleEvent::Away),
+                None,
+                // Бездействие началось за 300 секунд до паузы, а закончилось
+                // через 10 секунд после неё
+                Some(IdleEvent::Back(Duration::from_secs(310))),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_pause_if_not_working() {
+        let mut tracker = IdleTracker::default();
+        let mut source = FakeIdle::new(&[600, 900, 0]);
+
+        assert_eq!(
+            run(&mut tracker, &mut source, false),
+            vec![None, None, None]
+        );
+    }
+
+    #[test]
+    fn reset_drops_pause() {
+        let mut tracker = IdleTracker::default();
+        assert_eq!(
+            run(&mut tracker, &mut FakeIdle::new(&[600]), true),
+            vec![Some(IdleEvent::Away)]
+        );
+
+        // Пользователь сам запустил таймер - возвращение уже не отмечается
+        tracker.reset();
+        assert_eq!(
+            run(&mut tracker, &mut FakeIdle::new(&[0]), true),
+            vec![None]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Listening to systemd-logind signals (Linux only)
+//!
+//! When the PC goes to sleep or the session is locked, the user is obviously
+//! not working. TimeKeeper listens to `PrepareForSleep` of
+//! `org.freedesktop.login1.Manager` and to `Lock`/`Unlock` of the current
+//! `org.freedesktop.login1.Session` to count this time as a natural break.
+
+use anyhow::Result;
+use iced::futures::{SinkExt, Stream, StreamExt, channel::mpsc, stream};
+use zbus::{Connection, proxy, zvariant::OwnedObjectPath};
+
+/// Events of the user session received from logind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionEvent {
+    /// The system is going to sleep (suspend or hibernate)
+    Sleep,
+    /// The system has woken up
+    Wake,
+    /// The session was locked
+    Lock,
+    /// The session was unlocked
+    Unlock,
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    fn get_session(&self, session_id: &str) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(name = "GetSessionByPID")]
+    fn get_session_by_pid(&self, pid: u32) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+pub trait Session {
+    #[zbus(property)]
+    fn idle_hint(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn idle_since_hint(&self) -> zbus::Result<u64>;
+
+    #[zbus(signal)]
+    fn lock(&self) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn unlock(&self) -> zbus::Result<()>;
+}
+
+/// Blocking proxy of the current session (used to poll its properties)
+pub fn session_blocking(
+    conn: &zbus::blocking::Connection,
+) -> Result<SessionProxyBlocking<'static>> {
+    let manager = ManagerProxyBlocking::new(conn)?;
+    let session_path = match manager.get_session_by_pid(std::process::id()) {
+        Ok(path) => path,
+        Err(_) => manager.get_session("auto")?,
+    };
+
+    Ok(SessionProxyBlocking::builder(conn)
+        .path(session_path)?
+        .build()?)
+}
+
+/// Stream of session events from the system bus. Errors are printed to
+/// `stderr` and end the stream: TimeKeeper works without logind too
+pub fn events() -> impl Stream<Item = SessionEvent> {
+    iced::stream::channel(10, |mut output| async move {
+        let res = async {
+            let conn = Connection::system().await?;
+            listen(&conn, &mut output).await
+        };
+
+        if let Err(err) = res.await {
+            eprintln!("Failed to listen to logind signals:\n{err}");
+        }
+    })
+}
+
+/// Forwards logind signals received over `conn` to `output`
+///
+/// The connection is passed in from outside, so a private `dbus-daemon`
+/// which owns the `org.freedesktop.login1` name can stand in for the system
+/// bus.
+pub async fn listen(conn: &Connection, output: &mut mpsc::Sender<SessionEvent>) -> Result<()> {
+    let manager = ManagerProxy::new(conn).await?;
+
+    // Сигналы Lock/Unlock отправляются с реального пути сессии (например,
+    // `/org/freedesktop/login1/session/_32`), а не с `session/auto`, поэтому
+    // путь нужно узнать заранее.
+    let session_path = match manager.get_session_by_pid(std::process::id()).await {
+        Ok(path) => path,
+        Err(_) => manager.get_session("auto").await?,
+    };
+    let session = SessionProxy::builder(conn)
+        .path(session_path)?
+        .build()
+        .await?;
+
+    let sleep = manager
+        .receive_prepare_for_sleep()
+        .await?
+        .filter_map(|signal| async move {
+            let start = signal.args().ok()?.start;
+            Some(match start {
+                true => SessionEvent::Sleep,
+                false => SessionEvent::Wake,
+            })
+        });
+    let lock = session.receive_lock().await?.map(|_| SessionEvent::Lock);
+    let unlock = session
+        .receive_unlock()
+        .await?
+        .map(|_| SessionEvent::Unlock);
+
+    let mut events = std::pin::pin!(stream::select(sleep, stream::select(lock, unlock)));
+    while let Some(event) = events.next().await {
+        output.send(event).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        time::Duration,
+    };
+
+    use tokio::time::timeout;
+    use zbus::{connection, interface, zvariant::ObjectPath};
+
+    use super::*;
+
+    const MANAGER_PATH: &str = "/org/freedesktop/login1";
+    const SESSION_PATH: &str = "/org/freedesktop/login1/session/_1";
+
+    /// Stand-in for logind: it only tells the path of the session
+    struct FakeManager;
+
+    #[interface(name = "org.freedesktop.login1.Manager")]
+    impl FakeManager {
+        fn get_session(&self, _session_id: &str) -> OwnedObjectPath {
+            ObjectPath::from_static_str_unchecked(SESSION_PATH).into()
+        }
+
+        #[zbus(name = "GetSessionByPID")]
+        fn get_session_by_pid(&self, _pid: u32) -> OwnedObjectPath {
+            ObjectPath::from_static_str_unchecked(SESSION_PATH).into()
+        }
+    }
+
+    /// Private `dbus-daemon`. It is killed when dropped
+    struct Bus(Child);
+
+    impl Bus {
+        /// Starts the daemon and returns it with its address
+        fn start() -> (Self, String) {
+            let mut child = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("failed to start dbus-daemon");
+            let mut address = String::new();
+            BufReader::new(child.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+
+            (Self(child), address.trim().to_string())
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    /// Sends the logind signal which must become `event`
+    async fn emit(server: &Connection, event: SessionEvent) {
+        let (manager, session) = (
+            (MANAGER_PATH, "org.freedesktop.login1.Manager"),
+            (SESSION_PATH, "org.freedesktop.login1.Session"),
+        );
+        let res = match event {
+            SessionEvent::Sleep | SessionEvent::Wake => {
+                let start = event == SessionEvent::Sleep;
+                server
+                    .emit_signal(
+                        None::<&str>,
+                        manager.0,
+                        manager.1,
+                        "PrepareForSleep",
+                        &(start,),
+                    )
+                    .await
+            }
+            SessionEvent::Lock => {
+                server
+                    .emit_signal(None::<&str>, session.0, session.1, "Lock", &())
+                    .await
+            }
+            SessionEvent::Unlock => {
+                server
+                    .emit_signal(None::<&str>, session.0, session.1, "Unlock", &())
+                    .await
+            }
+        };
+        res.unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg_attr(not(dbus_daemon), ignore = "dbus-daemon is not installed")]
+    async fn signals_to_events() {
+        let (_bus, address) = Bus::start();
+        let server = connection::Builder::address(address.as_str())
+            .unwrap()
+            .name("org.freedesktop.login1")
+            .unwrap()
+            .serve_at(MANAGER_PATH, FakeManager)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = connection::Builder::address(address.as_str())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let (mut tx, mut rx) = mpsc::channel(10);
+        tokio::spawn(async move { listen(&client, &mut tx).await });
+
+        // Слушатель подписывается на сигналы не сразу. Unlock - последняя
+        // подписка, поэтому повторяем его, пока он не дойдёт
+        let mut is_ready = false;
+        for _ in 0..50 {
+            emit(&server, SessionEvent::Unlock).await;
+            if let Ok(Some(_)) = timeout(Duration::from_millis(100), rx.next()).await {
+                is_ready = true;
+                break;
+            }
+        }
+        assert!(is_ready, "the listener has not subscribed to the signals");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        while rx.try_recv().is_ok() {}
+
+        for event in [
+            SessionEvent::Sleep,
+            SessionEvent::Wake,
+            SessionEvent::Lock,
+            SessionEvent::Unlock,
+        ] {
+            emit(&server, event).await;
+            let received = timeout(Duration::from_secs(2), rx.next()).await;
+            assert_eq!(received.ok().flatten(), Some(event));
+        }
+    }
+}
@@ -11,14 +11,28 @@
 
 #![windows_subsystem = "windows"]
 
+mod aggregate;
 mod autostart;
 mod conf;
 mod consts;
+mod daily_limit;
+mod escalation;
 mod external_cmd;
 mod i18n;
+mod idle;
+#[cfg(target_os = "linux")]
+mod logind;
+mod micro_break;
 mod pathes;
+mod pause;
+mod profile;
+mod schedule;
+mod sequence;
 mod stats;
+mod stats_store;
+mod suggest;
 mod time;
+mod timer;
 mod traits;
 mod ui;
 
@@ -0,0 +1,248 @@
+//! Eye micro-breaks (e.g. the 20-20-20 rule)
+//!
+//! Micro-breaks run on their own schedule next to the main work/break cycle:
+//! after every `micro_break_interval` seconds of work the user looks away
+//! from the screen for `micro_break_time` seconds. They are suspended while
+//! the main timer is paused or the user is on a break.
+
+use std::time::{Duration, Instant};
+
+use crate::{conf::Config, time::Time, timer::Stopwatch};
+
+/// Events emitted by [`MicroBreaks`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MicroBreakEvent {
+    /// It's time to look away from the screen
+    Started,
+    /// The micro-break is over
+    Finished,
+}
+
+#[derive(Debug, Clone)]
+pub struct MicroBreaks {
+    /// Is there a micro-break now?
+    is_break: bool,
+
+    /// Time counted since the beginning of the interval or micro-break
+    stopwatch: Stopwatch,
+
+    /// Time between micro-breaks
+    interval: Time,
+
+    /// Micro-break time
+    length: Time,
+}
+
+impl MicroBreaks {
+    pub fn new(conf: &Config, now: Instant) -> Self {
+        Self {
+            is_break: false,
+            stopwatch: Stopwatch::started(now),
+            interval: conf.micro_break_interval,
+            length: conf.micro_break_time,
+        }
+    }
+
+    /// Change the durations. The new values are used immediately
+    pub fn set_intervals(&mut self, conf: &Config) {
+        self.interval = conf.micro_break_interval;
+        self.length = conf.micro_break_time;
+    }
+
+    pub fn is_break(&self) -> bool {
+        self.is_break
+    }
+
+    fn current_duration(&self) -> Duration {
+        match self.is_break {
+            true => self.length,
+            false => self.interval,
+        }
+        .to_duration()
+    }
+
+    /// Time left until the end of the interval or micro-break
+    pub fn remaining_time(&self, now: Instant) -> Time {
+        self.stopwatch.remaining(self.current_duration(), now)
+    }
+
+    /// Suspends counting (e.g. during the main break) or resumes it
+    pub fn set_suspended(&mut self, is_suspended: bool, now: Instant) {
+        match is_suspended {
+            true => self.stopwatch.pause(now),
+            false => self.stopwatch.resume(now),
+        }
+    }
+
+    /// Starts counting the interval anew (e.g. after the main break, when the
+    /// eyes have already rested). Ends the current micro-break, if any
+    pub fn reset(&mut self, now: Instant) -> Option<MicroBreakEvent> {
+        let was_break = self.is_break;
+
+        self.is_break = false;
+        self.stopwatch.restart(now);
+
+        was_break.then_some(MicroBreakEvent::Finished)
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Option<MicroBreakEvent> {
+        let deadline = self.stopwatch.deadline(self.current_duration())?;
+        if now < deadline {
+            return None;
+        }
+
+        // В отличие от основного цикла, микроперерыв отсчитывается от
+        // момента, когда пользователь узнал о нём, а не от дедлайна
+        self.is_break = !self.is_break;
+        self.stopwatch.restart(now);
+
+        Some(match self.is_break {
+            true => MicroBreakEvent::Started,
+            false => MicroBreakEvent::Finished,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::{Timer, TimerCommand, TimerEvent};
+
+    /// Micro-breaks of 20 seconds every 20 minutes, the main cycle of 60
+    /// minutes of work and 15 minutes of break without warnings
+    fn conf() -> Config {
+        Config {
+            micro_breaks: true,
+            break_warning: Time::ZERO,
+            break_final_warning: Time::ZERO,
+            ..Default::default()
+        }
+    }
+
+    fn secs(start: Instant, secs: u64) -> Instant {
+        start + Duration::from_secs(secs)
+    }
+
+    /// Ticks both timers every 10 seconds from `from` to `to` the way the UI
+    /// does and returns the micro-break events with their moments (seconds)
+    fn run(
+        micro: &mut MicroBreaks,
+        timer: &mut Timer,
+        t0: Instant,
+        from: u64,
+        to: u64,
+    ) -> Vec<(u64, MicroBreakEvent)> {
+        let mut events = Vec::new();
+        for s in (from..=to).step_by(10) {
+            let now = secs(t0, s);
+            for event in timer.handle(TimerCommand::Tick, now) {
+                if let TimerEvent::PhaseStarted { .. } = event {
+                    events.extend(micro.reset(now).map(|event| (s, event)));
+                }
+            }
+            micro.set_suspended(!timer.is_work() || timer.is_pause(), now);
+            events.extend(micro.tick(now).map(|event| (s, event)));
+        }
+        events
+    }
+
+    /// Sends `cmd` to the main timer and suspends or resumes the
+    /// micro-breaks at once
+    fn command(micro: &mut MicroBreaks, timer: &mut Timer, cmd: TimerCommand, now: Instant) {
+        timer.handle(cmd, now);
+        micro.set_suspended(!timer.is_work() || timer.is_pause(), now);
+    }
+
+    #[test]
+    fn every_interval_of_work() {
+        let t0 = Instant::now();
+        let mut micro = MicroBreaks::new(&conf(), t0);
+
+        assert_eq!(micro.tick(secs(t0, 1199)), None);
+        assert_eq!(micro.tick(secs(t0, 1200)), Some(MicroBreakEvent::Started));
+        assert!(micro.is_break());
+        assert_eq!(micro.remaining_time(secs(t0, 1200)), Time::from_secs(20));
+        assert_eq!(micro.tick(secs(t0, 1219)), None);
+        assert_eq!(micro.tick(secs(t0, 1220)), Some(MicroBreakEvent::Finished));
+        assert!(!micro.is_break());
+
+        // Следующий интервал - от конца микроперерыва
+        assert_eq!(micro.tick(secs(t0, 2419)), None);
+        assert_eq!(micro.tick(secs(t0, 2420)), Some(MicroBreakEvent::Started));
+    }
+
+    #[test]
+    fn late_tick_counts_from_notice() {
+        let t0 = Instant::now();
+        let mut micro = MicroBreaks::new(&conf(), t0);
+
+        assert_eq!(micro.tick(secs(t0, 1500)), Some(MicroBreakEvent::Started));
+        assert_eq!(micro.tick(secs(t0, 1510)), None);
+        assert_eq!(micro.tick(secs(t0, 1520)), Some(MicroBreakEvent::Finished));
+    }
+
+    #[test]
+    fn not_during_breaks() {
+        let t0 = Instant::now();
+        let mut micro = MicroBreaks::new(&conf(), t0);
+        let mut timer = Timer::new(&conf(), t0);
+
+        // Работа 0-60 мин, перерыв 60-75 мин, работа с 75 мин
+        assert_eq!(
+            run(&mut micro, &mut timer, t0, 0, 5400),
+            [
+                (1200, MicroBreakEvent::Started),
+                (1220, MicroBreakEvent::Finished),
+                (2420, MicroBreakEvent::Started),
+                (2440, MicroBreakEvent::Finished),
+            ]
+        );
+        // В 60 мин начался перерыв: отсчёт сбросился и стоял до начала
+        // работы в 75 мин
+        assert_eq!(micro.remaining_time(secs(t0, 5400)), Time::from_mins(5));
+    }
+
+    #[test]
+    fn not_during_pauses() {
+        let t0 = Instant::now();
+        let mut micro = MicroBreaks::new(&conf(), t0);
+        let mut timer = Timer::new(&conf(), t0);
+
+        assert!(run(&mut micro, &mut timer, t0, 0, 600).is_empty());
+        command(&mut micro, &mut timer, TimerCommand::Pause, secs(t0, 600));
+        assert!(run(&mut micro, &mut timer, t0, 610, 3600).is_empty());
+        assert_eq!(micro.remaining_time(secs(t0, 3600)), Time::from_mins(10));
+
+        command(&mut micro, &mut timer, TimerCommand::Start, secs(t0, 3600));
+        assert_eq!(
+            run(&mut micro, &mut timer, t0, 3610, 4200),
+            [(4200, MicroBreakEvent::Started)]
+        );
+    }
+
+    #[test]
+    fn reset_on_phase_change() {
+        let t0 = Instant::now();
+        let mut micro = MicroBreaks::new(&conf(), t0);
+        let mut timer = Timer::new(&conf(), t0);
+
+        // "Перерыв сейчас" во время микроперерыва заканчивает его
+        run(&mut micro, &mut timer, t0, 0, 1200);
+        assert!(micro.is_break());
+        let now = secs(t0, 1210);
+        let events = timer.handle(TimerCommand::Skip, now);
+        assert!(matches!(events[1], TimerEvent::PhaseStarted { .. }));
+        assert_eq!(micro.reset(now), Some(MicroBreakEvent::Finished));
+        assert!(!micro.is_break());
+
+        // После перерыва интервал отсчитывается заново
+        timer.handle(TimerCommand::Skip, secs(t0, 1300));
+        assert_eq!(micro.reset(secs(t0, 1300)), None);
+        micro.set_suspended(false, secs(t0, 1300));
+        assert_eq!(micro.remaining_time(secs(t0, 1300)), Time::from_mins(20));
+        assert_eq!(
+            run(&mut micro, &mut timer, t0, 1310, 2500),
+            [(2500, MicroBreakEvent::Started)]
+        );
+    }
+}
@@ -7,7 +7,9 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::consts::{PROG_CONF_PREFIX, PROG_PREFERENCES, PROG_STATISTICS};
+use crate::consts::{
+    PROG_CONF_PREFIX, PROG_PREFERENCES, PROG_STATISTICS, PROG_STATISTICS_DB, PROG_TIMER_STATE,
+};
 
 /// The function that checks if the necessary program files are present when
 /// the program is started and creates the necessary objects in case of their
@@ -32,7 +34,10 @@ pub enum ProgPath {
     ConfigPrefixDir,
     Preferences,
     Statistics,
+    StatisticsDb,
+    TimerState,
     CSVFile,
+    SummaryCSVFile,
 }
 
 impl ProgPath {
@@ -42,9 +47,14 @@ impl ProgPath {
             Self::ConfigPrefixDir => Self::HomeDir.get().join(PROG_CONF_PREFIX),
             Self::Preferences => Self::ConfigPrefixDir.get().join(PROG_PREFERENCES),
             Self::Statistics => Self::ConfigPrefixDir.get().join(PROG_STATISTICS),
+            Self::StatisticsDb => Self::ConfigPrefixDir.get().join(PROG_STATISTICS_DB),
+            Self::TimerState => Self::ConfigPrefixDir.get().join(PROG_TIMER_STATE),
             Self::CSVFile => home_dir()
                 .unwrap_or(Path::new(".").to_path_buf())
                 .join("TimeKeeper-statistics.csv"),
+            Self::SummaryCSVFile => home_dir()
+                .unwrap_or(Path::new(".").to_path_buf())
+                .join("TimeKeeper-summary.csv"),
         }
     }
 
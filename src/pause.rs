@@ -0,0 +1,45 @@
+//! Pause reasons
+//!
+//! The user picks why the timer is paused. Some reasons have a length: such
+//! a pause ends on its own and the timer resumes.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{fl, time::Time};
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    /// Meeting: 30 minutes
+    Meeting,
+    /// Lunch: 1 hour
+    Lunch,
+    /// The pause lasts until the user resumes the timer
+    #[default]
+    Manual,
+}
+
+impl PauseReason {
+    pub const ALL: [Self; 3] = [Self::Meeting, Self::Lunch, Self::Manual];
+
+    /// Pause length. `None` - until the user resumes the timer
+    pub fn duration(&self) -> Option<Time> {
+        match self {
+            Self::Meeting => Some(Time::from_mins(30)),
+            Self::Lunch => Some(Time::from_mins(60)),
+            Self::Manual => None,
+        }
+    }
+}
+
+impl Display for PauseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Meeting => fl!("pause_meeting"),
+            Self::Lunch => fl!("pause_lunch"),
+            Self::Manual => fl!("pause_manual"),
+        };
+        write!(f, "{name}")
+    }
+}
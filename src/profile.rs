@@ -0,0 +1,224 @@
+//! Named sets of intervals
+//!
+//! A profile replaces the work/break durations and break options of the
+//! settings. Besides the profiles from the config file, TimeKeeper ships a
+//! few [`presets`]. The profile with an empty name stands for the values set
+//! on the Preferences page.
+
+use std::fmt::Display;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::{conf::Config, fl, sequence::Sequence, time::Time};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Profile {
+    /// Name shown in the profile picker and saved to the statistics
+    pub name: String,
+
+    /// Work time
+    pub work_time: Time,
+
+    /// Free time
+    pub free_time: Time,
+
+    /// Long break time
+    pub long_break_time: Time,
+
+    /// A long break follows every `long_break_every` work cycles. `0`
+    /// disables long breaks
+    pub long_break_every: u8,
+
+    /// A break is postponed by this time
+    pub snooze_time: Time,
+
+    /// How many times a break can be postponed in one cycle
+    pub max_snoozes: u8,
+
+    /// User-defined phases. Empty - work and breaks alternate
+    #[serde(default, skip_serializing_if = "Sequence::is_empty")]
+    pub sequence: Sequence,
+}
+
+impl Profile {
+    /// The profile with the values from the Preferences page
+    pub fn custom(conf: &Config) -> Self {
+        Self {
+            name: String::new(),
+            work_time: conf.work_time,
+            free_time: conf.free_time,
+            long_break_time: conf.long_break_time,
+            long_break_every: conf.long_break_every,
+            snooze_time: conf.snooze_time,
+            max_snoozes: conf.max_snoozes,
+            sequence: conf.sequence.clone(),
+        }
+    }
+
+    /// Is it the profile with the values from the Preferences page?
+    pub fn is_custom(&self) -> bool {
+        self.name.is_empty()
+    }
+
+    /// Replaces the intervals and break options in `conf` with the ones of
+    /// this profile
+    pub fn apply(&self, conf: &mut Config) {
+        conf.work_time = self.work_time;
+        conf.free_time = self.free_time;
+        conf.long_break_time = self.long_break_time;
+        conf.long_break_every = self.long_break_every;
+        conf.snooze_time = self.snooze_time;
+        conf.max_snoozes = self.max_snoozes;
+        conf.sequence = self.sequence.clone();
+    }
+
+    /// Checks the intervals of the profile (see [`Config::validate`])
+    pub fn validate(&self) -> Result<()> {
+        let mut conf = Config::default();
+        self.apply(&mut conf);
+        conf.validate_intervals()
+            .map_err(|err| anyhow!("Profile '{}': {err}", self.name))
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.is_custom() {
+            true => write!(f, "{}", fl!("profile_custom")),
+            false => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Profiles shipped with TimeKeeper
+pub fn presets() -> Vec<Profile> {
+    vec![
+        // 4 "помидора" по 25 минут, затем длинный перерыв
+        Profile {
+            name: "Pomodoro 25/5".to_string(),
+            work_time: Time::from_mins(25),
+            free_time: Time::from_mins(5),
+            long_break_time: Time::from_mins(15),
+            long_break_every: 4,
+            snooze_time: Time::from_mins(5),
+            max_snoozes: 1,
+            sequence: Sequence::default(),
+        },
+        Profile {
+            name: "52/17".to_string(),
+            work_time: Time::from_mins(52),
+            free_time: Time::from_mins(17),
+            long_break_time: Time::from_mins(30),
+            long_break_every: 0,
+            snooze_time: Time::from_mins(5),
+            max_snoozes: 2,
+            sequence: Sequence::default(),
+        },
+        // Ультрадианный ритм: полтора часа глубокой работы
+        Profile {
+            name: "Ultradian 90/20".to_string(),
+            work_time: Time::from_mins(90),
+            free_time: Time::from_mins(20),
+            long_break_time: Time::from_mins(30),
+            long_break_every: 0,
+            snooze_time: Time::from_mins(10),
+            max_snoozes: 1,
+            sequence: Sequence::default(),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sequence::Phase, timer::IntervalPolicy};
+
+    /// The settings where every value differs from the default one
+    fn conf() -> Config {
+        let mut conf = Config {
+            work_time: Time::from_mins(45),
+            free_time: Time::from_mins(10),
+            long_break_time: Time::from_mins(25),
+            long_break_every: 3,
+            interval_policy: IntervalPolicy::Scale,
+            break_warning: Time::from_mins(2),
+            snooze_time: Time::from_mins(7),
+            max_snoozes: 4,
+            daily_limit: Time::from_mins(480),
+            micro_breaks: true,
+            strict_mode: true,
+            profile: "Deep work".to_string(),
+            ..Default::default()
+        };
+        conf.sequence.phases.push(Phase {
+            name: "Work".to_string(),
+            is_work: true,
+            time: Time::from_mins(40),
+            color: None,
+            notification: None,
+        });
+        conf
+    }
+
+    fn toml(conf: &Config) -> String {
+        toml::to_string(conf).unwrap()
+    }
+
+    #[test]
+    fn custom_keeps_settings() {
+        let conf = conf();
+        let custom = Profile::custom(&conf);
+        assert!(custom.is_custom());
+
+        let mut applied = Config::default();
+        custom.apply(&mut applied);
+        assert_eq!(applied.work_time, conf.work_time);
+        assert_eq!(applied.free_time, conf.free_time);
+        assert_eq!(applied.long_break_time, conf.long_break_time);
+        assert_eq!(applied.long_break_every, conf.long_break_every);
+        assert_eq!(applied.snooze_time, conf.snooze_time);
+        assert_eq!(applied.max_snoozes, conf.max_snoozes);
+        assert_eq!(applied.sequence, conf.sequence);
+        assert_eq!(Profile::custom(&applied), custom);
+    }
+
+    #[test]
+    fn apply_replaces_only_intervals() {
+        let preset = presets().remove(0);
+        let mut conf = conf();
+        let mut expected = conf.clone();
+        preset.apply(&mut conf);
+
+        // Всё, кроме интервалов и параметров перерывов, остаётся как было
+        expected.work_time = preset.work_time;
+        expected.free_time = preset.free_time;
+        expected.long_break_time = preset.long_break_time;
+        expected.long_break_every = preset.long_break_every;
+        expected.snooze_time = preset.snooze_time;
+        expected.max_snoozes = preset.max_snoozes;
+        expected.sequence = preset.sequence.clone();
+        assert_eq!(toml(&conf), toml(&expected));
+        assert_eq!(Profile::custom(&conf).sequence, Sequence::default());
+    }
+
+    #[test]
+    fn user_profile_before_preset() {
+        let preset = presets().remove(0);
+        let user = Profile {
+            work_time: Time::from_mins(30),
+            free_time: Time::from_mins(10),
+            ..preset.clone()
+        };
+        let mut conf = Config::default();
+        assert_eq!(conf.find_profile(&preset.name), Some(preset.clone()));
+
+        conf.profiles.push(user.clone());
+        assert_eq!(conf.find_profile(&preset.name), Some(user));
+        // Остальные готовые профили по-прежнему доступны
+        let other = presets().remove(1);
+        assert_eq!(conf.find_profile(&other.name), Some(other));
+        assert_eq!(conf.find_profile(""), None);
+        assert_eq!(conf.find_profile("Unknown"), None);
+    }
+}
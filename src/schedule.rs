@@ -0,0 +1,314 @@
+//! Working-hours schedule
+//!
+//! The schedule is a list of weekly time ranges ([`WorkRange`]). TimeKeeper
+//! starts the timer when a range begins and stops it when the range ends.
+//! Each range may have its own profile or work/break durations.
+
+use std::fmt::Display;
+
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::{conf::Config, time::Time};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Schedule {
+    /// Is the schedule used? If not, the timer works at any time
+    pub enabled: bool,
+
+    /// Working hours
+    pub ranges: Vec<WorkRange>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            // Обычная пятидневка с 9 до 18
+            ranges: vec![WorkRange {
+                days: vec![
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                ],
+                start: ClockTime::new(9, 0),
+                end: ClockTime::new(18, 0),
+                profile: None,
+                work_time: None,
+                free_time: None,
+            }],
+        }
+    }
+}
+
+impl Schedule {
+    /// Index of the range `now` falls into. `None` if it is off hours or the
+    /// schedule is disabled
+    pub fn active(&self, now: NaiveDateTime) -> Option<usize> {
+        if !self.enabled {
+            return None;
+        }
+        self.ranges.iter().position(|range| range.contains(now))
+    }
+}
+
+/// Time range on the given days of the week
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct WorkRange {
+    /// Days the range begins on
+    pub days: Vec<Weekday>,
+
+    /// Beginning of the range
+    pub start: ClockTime,
+
+    /// End of the range. If it is not later than `start`, the range ends on
+    /// the next day
+    pub end: ClockTime,
+
+    /// Name of the profile for this range. `None` - the active profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Work time for this range. `None` - from the profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_time: Option<Time>,
+
+    /// Free time for this range. `None` - from the profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub free_time: Option<Time>,
+}
+
+impl WorkRange {
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let time = ClockTime::new(now.hour() as u8, now.minute() as u8);
+        let today = Weekday::from(now.weekday());
+
+        if self.start < self.end {
+            self.days.contains(&today) && self.start <= time && time < self.end
+        } else {
+            // Ночной диапазон: начинается сегодня, а заканчивается завтра
+            let yesterday = Weekday::from(now.weekday().pred());
+            (self.days.contains(&today) && self.start <= time)
+                || (self.days.contains(&yesterday) && time < self.end)
+        }
+    }
+
+    /// Replaces the work/break durations in `conf` with the ones of this
+    /// range (its profile first, then the durations set for the range
+    /// itself)
+    pub fn apply(&self, conf: &mut Config) {
+        if let Some(profile) = self
+            .profile
+            .as_ref()
+            .and_then(|name| conf.find_profile(name))
+        {
+            profile.apply(conf);
+        }
+        if let Some(work_time) = self.work_time {
+            conf.work_time = work_time;
+        }
+        if let Some(free_time) = self.free_time {
+            conf.free_time = free_time;
+        }
+    }
+}
+
+impl Display for WorkRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = self
+            .days
+            .iter()
+            .map(|day| format!("{day:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{days}: {}-{}", self.start, self.end)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(day: chrono::Weekday) -> Self {
+        match day {
+            chrono::Weekday::Mon => Self::Mon,
+            chrono::Weekday::Tue => Self::Tue,
+            chrono::Weekday::Wed => Self::Wed,
+            chrono::Weekday::Thu => Self::Thu,
+            chrono::Weekday::Fri => Self::Fri,
+            chrono::Weekday::Sat => Self::Sat,
+            chrono::Weekday::Sun => Self::Sun,
+        }
+    }
+}
+
+/// Time of day with minute precision. Stored in the config as `"HH:MM"`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct ClockTime {
+    pub hour: u8,
+    pub min: u8,
+}
+
+impl ClockTime {
+    pub fn new(hour: u8, min: u8) -> Self {
+        Self { hour, min }
+    }
+}
+
+impl TryFrom<String> for ClockTime {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let (hour, min) = value
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Time '{value}' must be in the 'HH:MM' format"))?;
+        let (hour, min) = (hour.trim().parse::<u8>()?, min.trim().parse::<u8>()?);
+
+        if hour > 23 || min > 59 {
+            return Err(anyhow!("Time '{value}' is out of range"));
+        }
+        Ok(Self { hour, min })
+    }
+}
+
+impl From<ClockTime> for String {
+    fn from(value: ClockTime) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for ClockTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// 10 July 2025 is a Thursday
+    fn thu(hour: u32, min: u32) -> NaiveDateTime {
+        at(10, hour, min)
+    }
+
+    fn at(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 7, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    fn range(days: &[Weekday], start: (u8, u8), end: (u8, u8)) -> WorkRange {
+        WorkRange {
+            days: days.to_vec(),
+            start: ClockTime::new(start.0, start.1),
+            end: ClockTime::new(end.0, end.1),
+            profile: None,
+            work_time: None,
+            free_time: None,
+        }
+    }
+
+    #[test]
+    fn day_range() {
+        let range = range(&[Weekday::Thu], (9, 0), (18, 0));
+        assert!(!range.contains(thu(8, 59)));
+        assert!(range.contains(thu(9, 0)));
+        assert!(range.contains(thu(17, 59)));
+        // Конец диапазона в него не входит
+        assert!(!range.contains(thu(18, 0)));
+        // Пятница не входит
+        assert!(!range.contains(at(11, 10, 0)));
+    }
+
+    #[test]
+    fn overnight_range() {
+        // Ночная смена начинается в четверг, а заканчивается в пятницу
+        let range = range(&[Weekday::Thu], (22, 0), (6, 0));
+        assert!(!range.contains(thu(21, 59)));
+        assert!(range.contains(thu(22, 0)));
+        assert!(range.contains(thu(23, 59)));
+        assert!(range.contains(at(11, 0, 0)));
+        assert!(range.contains(at(11, 5, 59)));
+        assert!(!range.contains(at(11, 6, 0)));
+
+        // Утро четверга - конец смены среды, которой нет
+        assert!(!range.contains(thu(0, 0)));
+        assert!(!range.contains(thu(5, 0)));
+        // Вечер пятницы - начало смены пятницы, которой тоже нет
+        assert!(!range.contains(at(11, 22, 0)));
+    }
+
+    #[test]
+    fn overnight_range_across_week() {
+        // Смена с воскресенья на понедельник
+        let range = range(&[Weekday::Sun], (22, 0), (6, 0));
+        assert!(range.contains(at(13, 23, 0)));
+        assert!(range.contains(at(14, 1, 0)));
+        assert!(!range.contains(at(14, 22, 0)));
+    }
+
+    #[test]
+    fn equal_start_and_end() {
+        // Конец не позже начала - диапазон длится сутки, до того же времени
+        // следующего дня
+        let range = range(&[Weekday::Thu], (8, 0), (8, 0));
+        assert!(!range.contains(thu(7, 59)));
+        assert!(range.contains(thu(8, 0)));
+        assert!(range.contains(thu(23, 0)));
+        assert!(range.contains(at(11, 7, 59)));
+        assert!(!range.contains(at(11, 8, 0)));
+    }
+
+    #[test]
+    fn active_range() {
+        let mut schedule = Schedule {
+            enabled: true,
+            ranges: vec![
+                range(&[Weekday::Thu], (9, 0), (12, 0)),
+                range(&[Weekday::Thu], (13, 0), (18, 0)),
+            ],
+        };
+        assert_eq!(schedule.active(thu(10, 0)), Some(0));
+        assert_eq!(schedule.active(thu(12, 30)), None);
+        assert_eq!(schedule.active(thu(13, 0)), Some(1));
+
+        schedule.enabled = false;
+        assert_eq!(schedule.active(thu(10, 0)), None);
+    }
+
+    #[test]
+    fn parse_clock_time() {
+        let parse = |s: &str| ClockTime::try_from(s.to_string());
+        assert_eq!(parse("09:30").unwrap(), ClockTime::new(9, 30));
+        assert_eq!(parse("9:05").unwrap(), ClockTime::new(9, 5));
+        assert_eq!(parse(" 23 : 59 ").unwrap(), ClockTime::new(23, 59));
+        assert_eq!(parse("0:00").unwrap(), ClockTime::new(0, 0));
+        for s in ["", "930", "24:00", "12:60", "-1:00", "ab:cd", "12:30:00"] {
+            assert!(parse(s).is_err(), "'{s}' must not be parsed");
+        }
+
+        assert_eq!(ClockTime::new(9, 5).to_string(), "09:05");
+        let range: WorkRange =
+            toml::from_str("days = [\"Mon\", \"Sun\"]\nstart = \"22:00\"\nend = \"06:00\"")
+                .unwrap();
+        assert_eq!(range.days, [Weekday::Mon, Weekday::Sun]);
+        assert_eq!(range.start, ClockTime::new(22, 0));
+        assert_eq!(range.end, ClockTime::new(6, 0));
+    }
+}
@@ -0,0 +1,94 @@
+//! User-defined phase sequences
+//!
+//! By default the timer just alternates work and breaks. A sequence from the
+//! config replaces this with an arbitrary cycle of named phases:
+//!
+//! ```toml
+//! [sequence]
+//! repeat = true
+//!
+//! [[sequence.phases]]
+//! name = "Work"
+//! is_work = true
+//! time = "50m"
+//!
+//! [[sequence.phases]]
+//! name = "Stretch"
+//! is_work = false
+//! time = "5m"
+//! color = "#458588"
+//! notification = "Stand up and stretch your back"
+//!
+//! [[sequence.phases]]
+//! name = "Work"
+//! is_work = true
+//! time = "50m"
+//!
+//! [[sequence.phases]]
+//! name = "Walk"
+//! is_work = false
+//! time = "15m"
+//! ```
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::time::Time;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Sequence {
+    /// Phases of the cycle. Empty - work and breaks alternate
+    pub phases: Vec<Phase>,
+
+    /// Does the sequence start again after the last phase? If not, the timer
+    /// stops
+    pub repeat: bool,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self {
+            phases: Vec::new(),
+            repeat: true,
+        }
+    }
+}
+
+impl Sequence {
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+
+    /// Every phase must last some time: a zero-length phase ends at once,
+    /// and a cycle of such phases never ends
+    pub fn validate(&self) -> Result<()> {
+        match self.phases.iter().find(|phase| phase.time.is_zero()) {
+            Some(phase) => Err(anyhow!("Phase '{}' has zero time", phase.name)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Phase {
+    /// Label shown on the main page and saved to the statistics
+    pub name: String,
+
+    /// Is it a work phase? Breaks can be postponed, micro-breaks and idle
+    /// detection work only during work phases
+    pub is_work: bool,
+
+    /// Phase time (seconds or a string like `"50m"`)
+    pub time: Time,
+
+    /// Background colour of the main page (e.g. `"#458588"`). `None` - the
+    /// colour of work or break
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+
+    /// Text of the notification sent when the phase begins. `None` - the
+    /// default text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification: Option<String>,
+}
@@ -1,35 +1,303 @@
 //! Statistics collection
+//!
+//! [`Stats`] keeps the recent entries shown by the UI. The whole history is
+//! written to the database ([`StatsStore`]); without it the statistics are
+//! saved to `stat.toml` as before.
+
+use std::{fmt::Write, ops::Range};
 
 use crate::{
+    escalation::EscalationLevel,
+    pause::PauseReason,
+    stats_store::{EventRecord, PhaseKind, StatsStore},
     time::{Time, fmt_date},
+    timer::PhaseOutcome,
     traits::Toml,
 };
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// The whole history in the queries (SQLite stores timestamps as `i64`)
+pub const ALL_TIME: Range<u64> = 0..i64::MAX as u64;
+
+/// How many entries of each kind are kept in memory
+const RECENT_LEN: usize = 10;
+
+/// The current version of the entry schema:
+///
+/// 1. The end of a phase (`date`), its type and duration.
+/// 2. The start of a phase, its planned duration and outcome. The end is
+///    called `end`.
+pub const STATS_VERSION: u32 = 2;
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Stats {
+    /// Version of the entry schema. Files without it have the version 1
+    #[serde(default = "first_version")]
+    pub version: u32,
+
+    #[serde(default)]
     pub stats: Vec<StatisticEntry>,
+
+    /// Periods when the user was idle and the work timer was paused
+    /// automatically
+    #[serde(default)]
+    pub idle: Vec<IdleEntry>,
+
+    /// Postponed breaks
+    #[serde(default)]
+    pub postponed: Vec<PostponeEntry>,
+
+    /// Strict breaks skipped with the emergency phrase
+    #[serde(default)]
+    pub emergency_skips: Vec<EmergencySkipEntry>,
+
+    /// Ends of the working hours
+    #[serde(default)]
+    pub day_ends: Vec<DayEndEntry>,
+
+    /// Pauses made by the user
+    #[serde(default)]
+    pub pauses: Vec<PauseEntry>,
+
+    /// The database with the whole history. `None` - the database couldn't
+    /// be opened
+    #[serde(skip)]
+    store: Option<StatsStore>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+fn first_version() -> u32 {
+    1
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            version: STATS_VERSION,
+            stats: Vec::new(),
+            idle: Vec::new(),
+            postponed: Vec::new(),
+            emergency_skips: Vec::new(),
+            day_ends: Vec::new(),
+            pauses: Vec::new(),
+            store: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct StatisticEntry {
-    /// Date&time in UNIX Timestamp format
-    pub date: u64,
+    /// Date&time the phase began in UNIX Timestamp format
+    #[serde(default)]
+    pub start: u64,
+
+    /// Date&time the phase ended in UNIX Timestamp format (`date` in the
+    /// version 1)
+    #[serde(alias = "date")]
+    pub end: u64,
 
     /// Work or freetime?
     pub is_wtime: bool,
 
+    /// Was it a long break?
+    #[serde(default)]
+    pub is_long_break: bool,
+
+    /// How long the phase should have lasted. `None` - unknown (version 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planned: Option<Time>,
+
     /// The time that has passed during this phase
-    pub time: u16,
+    pub time: Time,
+
+    /// How the phase ended. Phases of the version 1 are counted as finished
+    #[serde(default)]
+    pub outcome: PhaseOutcome,
+
+    /// Name of the phase from the phase sequence. Empty - work or break
+    #[serde(default)]
+    pub phase: String,
+
+    /// Name of the profile the phase was in. Empty - the intervals from the
+    /// settings
+    #[serde(default)]
+    pub profile: String,
+
+    /// How far the reminder about the break escalated. `None` for work
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<EscalationLevel>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct IdleEntry {
+    /// Date&time (the user came back) in UNIX Timestamp format
+    pub date: u64,
+
+    /// How long the user was idle
+    pub time: Time,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct PostponeEntry {
+    /// Date&time (the break was postponed) in UNIX Timestamp format
+    pub date: u64,
+
+    /// The break was postponed by this time
+    pub time: Time,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct EmergencySkipEntry {
+    /// Date&time (the break was skipped) in UNIX Timestamp format
+    pub date: u64,
+
+    /// The break time that was left
+    pub time: Time,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct DayEndEntry {
+    /// Date&time (the working hours ended) in UNIX Timestamp format
+    pub date: u64,
+
+    /// Work time during these working hours
+    pub time: Time,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct PauseEntry {
+    /// Date&time (the pause ended) in UNIX Timestamp format
+    pub date: u64,
+
+    /// How long the pause lasted
+    pub time: Time,
+
+    /// Why the timer was paused
+    pub reason: PauseReason,
 }
 
 impl Toml for Stats {}
 
 impl Stats {
+    /// Loads the recent entries from the database: the last ones of each
+    /// kind and all phases finished since `keep_since` (UNIX Timestamp)
+    pub fn with_store(store: StatsStore, keep_since: u64) -> Result<Self> {
+        Ok(Self {
+            version: STATS_VERSION,
+            stats: store.recent_phases(RECENT_LEN, keep_since)?,
+            idle: store.recent_events(RECENT_LEN)?,
+            postponed: store.recent_events(RECENT_LEN)?,
+            emergency_skips: store.recent_events(RECENT_LEN)?,
+            day_ends: store.recent_events(RECENT_LEN)?,
+            pauses: store.recent_events(RECENT_LEN)?,
+            store: Some(store),
+        })
+    }
+
+    /// Brings the entries read from an old file up to the current schema
+    pub fn migrate(&mut self) {
+        if self.version < 2 {
+            // Начало фазы не сохранялось - восстанавливаем его по длительности
+            for entry in &mut self.stats {
+                entry.start = entry.end.saturating_sub(entry.time.to_secs() as u64);
+            }
+        }
+        self.version = STATS_VERSION;
+    }
+
+    /// Is every entry saved to the database at once?
+    pub fn has_store(&self) -> bool {
+        self.store.is_some()
+    }
+
+    fn store_write(&self, write: impl FnOnce(&StatsStore) -> Result<()>) {
+        if let Some(store) = &self.store
+            && let Err(err) = write(store)
+        {
+            eprintln!("Failed to write the statistics database:\n{err}");
+        }
+    }
+
     pub fn push(&mut self, entry: StatisticEntry) {
+        self.store_write(|store| store.insert_phase(&entry));
         self.stats.push(entry);
     }
 
+    pub fn push_idle(&mut self, entry: IdleEntry) {
+        self.store_write(|store| store.insert_event(&entry));
+        self.idle.push(entry);
+    }
+
+    pub fn push_postponed(&mut self, entry: PostponeEntry) {
+        self.store_write(|store| store.insert_event(&entry));
+        self.postponed.push(entry);
+    }
+
+    pub fn push_emergency_skip(&mut self, entry: EmergencySkipEntry) {
+        self.store_write(|store| store.insert_event(&entry));
+        self.emergency_skips.push(entry);
+    }
+
+    pub fn push_day_end(&mut self, entry: DayEndEntry) {
+        self.store_write(|store| store.insert_event(&entry));
+        self.day_ends.push(entry);
+    }
+
+    pub fn push_pause(&mut self, entry: PauseEntry) {
+        self.store_write(|store| store.insert_event(&entry));
+        self.pauses.push(entry);
+    }
+
+    /// Removes the recent entries and the whole history in the database
+    pub fn clear(&mut self) {
+        self.store_write(|store| store.clear());
+        self.stats.clear();
+        self.idle.clear();
+        self.postponed.clear();
+        self.emergency_skips.clear();
+        self.day_ends.clear();
+        self.pauses.clear();
+    }
+
+    /// Phases finished in `range` (UNIX Timestamp) in chronological order.
+    /// Empty `kinds` - phases of all types. Without the database only the
+    /// recent phases are available
+    pub fn phases(&self, range: Range<u64>, kinds: &[PhaseKind]) -> Vec<StatisticEntry> {
+        let from_store = self
+            .store
+            .as_ref()
+            .map(|store| store.phases(range.clone(), kinds));
+        match from_store {
+            Some(Ok(entries)) => return entries,
+            Some(Err(err)) => eprintln!("Failed to read the statistics database:\n{err}"),
+            None => {}
+        }
+
+        self.stats
+            .iter()
+            .filter(|entry| range.contains(&entry.end))
+            .filter(|entry| kinds.is_empty() || kinds.contains(&PhaseKind::of(entry)))
+            .cloned()
+            .collect()
+    }
+
+    /// Events of the type `E` in `range` (UNIX Timestamp) in chronological
+    /// order. Without the database only the recent events are available
+    pub fn events<E: EventRecord>(&self, range: Range<u64>) -> Vec<E> {
+        let from_store = self.store.as_ref().map(|store| store.events(range.clone()));
+        match from_store {
+            Some(Ok(entries)) => return entries,
+            Some(Err(err)) => eprintln!("Failed to read the statistics database:\n{err}"),
+            None => {}
+        }
+
+        E::recent(self)
+            .iter()
+            .filter(|entry| range.contains(&entry.date()))
+            .cloned()
+            .collect()
+    }
+
     pub fn len(&self) -> usize {
         self.stats.len()
     }
@@ -42,10 +310,22 @@ impl Stats {
         self.stats.is_empty()
     }
 
-    pub fn remove_unneeded(&mut self) {
+    /// Work time of the phases finished since `since` (UNIX Timestamp)
+    pub fn work_time_since(&self, since: u64) -> Time {
+        self.stats
+            .iter()
+            .filter(|entry| entry.is_wtime && entry.end >= since)
+            .map(|entry| entry.time)
+            .sum()
+    }
+
+    /// Keeps the last 10 entries of each kind in memory (the database keeps
+    /// the whole history). Phases finished since `keep_since` (UNIX
+    /// Timestamp) are kept anyway: the daily work limit is counted by them
+    pub fn remove_unneeded(&mut self, keep_since: u64) {
         let mut len = self.len();
-        if len > 10 {
-            while len > 10 {
+        if len > RECENT_LEN {
+            while len > RECENT_LEN && self.stats[0].end < keep_since {
                 // Это может быть слишком медленным для больших векторов,
                 // однако мы постараемся не допускать разрастания вектора
                 // больше 10 элементов. Может быть, вместо этого лучше
@@ -54,26 +334,179 @@ impl Stats {
                 len -= 1;
             }
         }
+
+        if self.idle.len() > RECENT_LEN {
+            self.idle.drain(..self.idle.len() - RECENT_LEN);
+        }
+        if self.postponed.len() > RECENT_LEN {
+            self.postponed.drain(..self.postponed.len() - RECENT_LEN);
+        }
+        if self.emergency_skips.len() > RECENT_LEN {
+            self.emergency_skips
+                .drain(..self.emergency_skips.len() - RECENT_LEN);
+        }
+        if self.day_ends.len() > RECENT_LEN {
+            self.day_ends.drain(..self.day_ends.len() - RECENT_LEN);
+        }
+        if self.pauses.len() > RECENT_LEN {
+            self.pauses.drain(..self.pauses.len() - RECENT_LEN);
+        }
     }
 
+    /// CSV with the whole history
     pub fn gen_csv(&self) -> String {
-        let mut csv = format!("duration;date;type");
-        for entry in &self.stats {
-            let e_str = format!(
-                "{};{};{}",
-                Time::from_secs(entry.time),
+        // Запись в String не может завершиться ошибкой, поэтому результат
+        // `writeln!` игнорируется
+        let mut csv = "duration;date;type;profile;start;planned;outcome\n".to_string();
+        for entry in &self.phases(ALL_TIME, &[]) {
+            let _ = writeln!(
+                csv,
+                "{};{};{};{};{};{};{:?}",
+                entry.time,
+                fmt_date(entry.end),
+                match (entry.is_wtime, entry.is_long_break) {
+                    _ if !entry.phase.is_empty() => &entry.phase,
+                    (true, _) => "work",
+                    (false, false) => "break",
+                    (false, true) => "long break",
+                },
+                entry.profile,
+                fmt_date(entry.start),
+                entry
+                    .planned
+                    .map(|time| time.to_string())
+                    .unwrap_or_default(),
+                entry.outcome,
+            );
+        }
+        for entry in &self.events::<IdleEntry>(ALL_TIME) {
+            let _ = writeln!(csv, "{};{};idle;", entry.time, fmt_date(entry.date));
+        }
+        for entry in &self.events::<PostponeEntry>(ALL_TIME) {
+            let _ = writeln!(csv, "{};{};postponed;", entry.time, fmt_date(entry.date));
+        }
+        for entry in &self.events::<EmergencySkipEntry>(ALL_TIME) {
+            let _ = writeln!(
+                csv,
+                "{};{};emergency skip;",
+                entry.time,
+                fmt_date(entry.date)
+            );
+        }
+        for entry in &self.events::<DayEndEntry>(ALL_TIME) {
+            let _ = writeln!(csv, "{};{};end of day;", entry.time, fmt_date(entry.date));
+        }
+        for entry in &self.events::<PauseEntry>(ALL_TIME) {
+            let _ = writeln!(
+                csv,
+                "{};{};pause: {:?};",
+                entry.time,
                 fmt_date(entry.date),
-                if entry.is_wtime { "work" } else { "break" },
+                entry.reason,
             );
-            csv = format!("{csv}\n{e_str}");
         }
-
         csv
     }
 }
 
-impl Default for Stats {
-    fn default() -> Self {
-        Self { stats: Vec::new() }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_first_version() {
+        // stat.toml версии 1: без `version`, конец фазы называется `date`
+        let mut stats: Stats = toml::from_str(
+            r#"
+            [[stats]]
+            date = 5000
+            is_wtime = true
+            time = 1500
+
+            [[stats]]
+            date = 5300
+            is_wtime = false
+            time = 300
+
+            [[stats]]
+            date = 100
+            is_wtime = true
+            time = 600
+            "#,
+        )
+        .unwrap();
+        assert_eq!(stats.version, 1);
+
+        stats.migrate();
+        assert_eq!(stats.version, STATS_VERSION);
+        let migrated = stats
+            .stats
+            .iter()
+            .map(|entry| (entry.start, entry.end, entry.planned, entry.outcome))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            migrated,
+            [
+                (3500, 5000, None, PhaseOutcome::Finished),
+                (5000, 5300, None, PhaseOutcome::Finished),
+                (0, 100, None, PhaseOutcome::Finished),
+            ]
+        );
+        assert!(stats.stats.iter().all(|entry| !entry.is_long_break));
+        assert!(stats.stats.iter().all(|entry| entry.profile.is_empty()));
+    }
+
+    #[test]
+    fn migrate_keeps_current_version() {
+        let mut stats: Stats = toml::from_str(
+            r#"
+            version = 2
+
+            [[stats]]
+            start = 4000
+            end = 5000
+            is_wtime = true
+            time = 900
+            outcome = "Stopped"
+            "#,
+        )
+        .unwrap();
+
+        stats.migrate();
+        // Начало уже известно и не пересчитывается по длительности
+        assert_eq!(stats.stats[0].start, 4000);
+        assert_eq!(stats.stats[0].outcome, PhaseOutcome::Stopped);
+    }
+
+    #[test]
+    fn csv_has_line_per_entry() {
+        let mut stats = Stats::default();
+        stats.push(StatisticEntry {
+            start: 0,
+            end: 1500,
+            is_wtime: true,
+            is_long_break: false,
+            planned: Some(Time::from_mins(25)),
+            time: Time::from_mins(25),
+            outcome: PhaseOutcome::Finished,
+            phase: String::new(),
+            profile: String::new(),
+            escalation: None,
+        });
+        stats.push_idle(IdleEntry {
+            date: 2000,
+            time: Time::from_secs(90),
+        });
+
+        let csv = stats.gen_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "duration;date;type;profile;start;planned;outcome",
+                "0:25:00;01.01 00:25;work;;01.01 00:00;0:25:00;Finished",
+                "0:01:30;01.01 00:33;idle;",
+            ]
+        );
     }
 }
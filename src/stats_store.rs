@@ -0,0 +1,721 @@
+//! SQLite storage of the statistics
+//!
+//! Unlike `stat.toml`, the database keeps the whole history: the [`Stats`]
+//! facade holds only the recent entries for the UI. Every entry is written
+//! at once, so a crash loses nothing. The entries of the old `stat.toml` are
+//! imported on the first run.
+
+use std::{ops::Range, path::Path};
+
+use anyhow::{Result, anyhow};
+use rusqlite::{
+    Connection, OptionalExtension, ToSql, params,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+};
+
+use crate::{
+    escalation::EscalationLevel,
+    pause::PauseReason,
+    stats::{
+        DayEndEntry, EmergencySkipEntry, IdleEntry, PauseEntry, PostponeEntry, STATS_VERSION,
+        StatisticEntry, Stats,
+    },
+    time::Time,
+    timer::PhaseOutcome,
+};
+
+/// Version of the database schema (`PRAGMA user_version`). It matches the
+/// version of the entries ([`STATS_VERSION`])
+const SCHEMA_VERSION: u32 = STATS_VERSION;
+
+/// The schema of a new database. `date` of a phase is its end
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS phases (
+        id INTEGER PRIMARY KEY,
+        start INTEGER NOT NULL,
+        date INTEGER NOT NULL,
+        is_work INTEGER NOT NULL,
+        is_long_break INTEGER NOT NULL,
+        planned INTEGER,
+        time INTEGER NOT NULL,
+        outcome TEXT NOT NULL,
+        phase TEXT NOT NULL,
+        profile TEXT NOT NULL,
+        escalation TEXT
+    );
+    CREATE INDEX IF NOT EXISTS phases_date ON phases (date);
+
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY,
+        kind TEXT NOT NULL,
+        date INTEGER NOT NULL,
+        time INTEGER NOT NULL,
+        reason TEXT
+    );
+    CREATE INDEX IF NOT EXISTS events_kind_date ON events (kind, date);
+
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+/// Migrations of an existing database: the item `i` brings the version
+/// `i + 1` up to `i + 2`
+const MIGRATIONS: [&str; SCHEMA_VERSION as usize - 1] = [
+    // Версия 2: начало фазы, запланированная длительность и итог
+    "
+    ALTER TABLE phases ADD COLUMN start INTEGER NOT NULL DEFAULT 0;
+    UPDATE phases SET start = MAX(date - time, 0);
+    ALTER TABLE phases ADD COLUMN planned INTEGER;
+    ALTER TABLE phases ADD COLUMN outcome TEXT NOT NULL DEFAULT 'finished';
+    ",
+];
+
+/// Key in the `meta` table: `stat.toml` has been imported
+const META_IMPORTED: &str = "toml_imported";
+
+const PHASE_COLUMNS: &str =
+    "start, date, is_work, is_long_break, planned, time, outcome, phase, profile, escalation";
+
+/// Type of a phase in the queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhaseKind {
+    Work,
+    Break,
+    LongBreak,
+}
+
+impl PhaseKind {
+    pub fn of(entry: &StatisticEntry) -> Self {
+        match (entry.is_wtime, entry.is_long_break) {
+            (true, _) => Self::Work,
+            (false, false) => Self::Break,
+            (false, true) => Self::LongBreak,
+        }
+    }
+
+    fn condition(self) -> &'static str {
+        match self {
+            Self::Work => "is_work = 1",
+            Self::Break => "(is_work = 0 AND is_long_break = 0)",
+            Self::LongBreak => "(is_work = 0 AND is_long_break = 1)",
+        }
+    }
+}
+
+/// Entries stored in the `events` table: everything except phases
+pub trait EventRecord: Clone {
+    /// Value of the `kind` column
+    const KIND: &'static str;
+
+    fn date(&self) -> u64;
+    fn time(&self) -> Time;
+    fn reason(&self) -> Option<PauseReason> {
+        None
+    }
+    fn from_row(date: u64, time: Time, reason: Option<PauseReason>) -> Self;
+
+    /// The recent entries of this kind kept by [`Stats`]
+    fn recent(stats: &Stats) -> &[Self];
+}
+
+macro_rules! event_record {
+    ($entry:ty, $kind:literal, $field:ident) => {
+        impl EventRecord for $entry {
+            const KIND: &'static str = $kind;
+
+            fn date(&self) -> u64 {
+                self.date
+            }
+
+            fn time(&self) -> Time {
+                self.time
+            }
+
+            fn from_row(date: u64, time: Time, _: Option<PauseReason>) -> Self {
+                Self { date, time }
+            }
+
+            fn recent(stats: &Stats) -> &[Self] {
+                &stats.$field
+            }
+        }
+    };
+}
+
+event_record!(IdleEntry, "idle", idle);
+event_record!(PostponeEntry, "postponed", postponed);
+event_record!(EmergencySkipEntry, "emergency_skip", emergency_skips);
+event_record!(DayEndEntry, "day_end", day_ends);
+
+impl EventRecord for PauseEntry {
+    const KIND: &'static str = "pause";
+
+    fn date(&self) -> u64 {
+        self.date
+    }
+
+    fn time(&self) -> Time {
+        self.time
+    }
+
+    fn reason(&self) -> Option<PauseReason> {
+        Some(self.reason)
+    }
+
+    fn from_row(date: u64, time: Time, reason: Option<PauseReason>) -> Self {
+        Self {
+            date,
+            time,
+            reason: reason.unwrap_or_default(),
+        }
+    }
+
+    fn recent(stats: &Stats) -> &[Self] {
+        &stats.pauses
+    }
+}
+
+#[derive(Debug)]
+pub struct StatsStore {
+    conn: Connection,
+}
+
+impl StatsStore {
+    /// Opens the database (and creates it if it doesn't exist). A database
+    /// of an older version is migrated
+    pub fn open<P: AsRef<Path>>(pth: P) -> Result<Self> {
+        Self::with_connection(Connection::open(pth)?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        let tx = conn.transaction()?;
+        match version {
+            0 => tx.execute_batch(SCHEMA)?,
+            SCHEMA_VERSION => {}
+            _ if version > SCHEMA_VERSION => {
+                return Err(anyhow!(
+                    "The statistics database has the version {version}, but only \
+                     {SCHEMA_VERSION} is supported. Update TimeKeeper"
+                ));
+            }
+            _ => {
+                for migration in &MIGRATIONS[version as usize - 1..] {
+                    tx.execute_batch(migration)?;
+                }
+            }
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
+
+        Ok(Self { conn })
+    }
+
+    /// Have the entries of `stat.toml` been imported?
+    pub fn is_imported(&self) -> Result<bool> {
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                [META_IMPORTED],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value.is_some())
+    }
+
+    /// Imports all entries of `stats` in one transaction: either all of
+    /// them or none get into the database
+    pub fn import(&mut self, stats: &Stats) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for entry in &stats.stats {
+            insert_phase(&tx, entry)?;
+        }
+        insert_events(&tx, &stats.idle)?;
+        insert_events(&tx, &stats.postponed)?;
+        insert_events(&tx, &stats.emergency_skips)?;
+        insert_events(&tx, &stats.day_ends)?;
+        insert_events(&tx, &stats.pauses)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, '1')",
+            [META_IMPORTED],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn insert_phase(&self, entry: &StatisticEntry) -> Result<()> {
+        insert_phase(&self.conn, entry)
+    }
+
+    pub fn insert_event<E: EventRecord>(&self, entry: &E) -> Result<()> {
+        insert_events(&self.conn, std::slice::from_ref(entry))
+    }
+
+    /// Phases finished in `range` (UNIX Timestamp) in chronological order.
+    /// Empty `kinds` - phases of all types
+    pub fn phases(&self, range: Range<u64>, kinds: &[PhaseKind]) -> Result<Vec<StatisticEntry>> {
+        let kinds = match kinds.is_empty() {
+            true => "1".to_string(),
+            false => kinds
+                .iter()
+                .map(|kind| kind.condition())
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        };
+        let sql = format!(
+            "SELECT {PHASE_COLUMNS} FROM phases
+             WHERE date >= ?1 AND date < ?2 AND ({kinds})
+             ORDER BY date, id"
+        );
+        self.query_phases(&sql, params![range.start, range.end])
+    }
+
+    /// The last `limit` phases plus all phases finished since `since`
+    pub fn recent_phases(&self, limit: usize, since: u64) -> Result<Vec<StatisticEntry>> {
+        let sql = format!(
+            "SELECT {PHASE_COLUMNS} FROM phases
+             WHERE date >= ?1
+                OR id IN (SELECT id FROM phases ORDER BY date DESC, id DESC LIMIT ?2)
+             ORDER BY date, id"
+        );
+        self.query_phases(&sql, params![since, limit])
+    }
+
+    /// Events of the type `E` in `range` (UNIX Timestamp) in chronological
+    /// order
+    pub fn events<E: EventRecord>(&self, range: Range<u64>) -> Result<Vec<E>> {
+        self.query_events(
+            "SELECT date, time, reason FROM events
+             WHERE kind = ?1 AND date >= ?2 AND date < ?3
+             ORDER BY date, id",
+            params![E::KIND, range.start, range.end],
+        )
+    }
+
+    /// The last `limit` events of the type `E`
+    pub fn recent_events<E: EventRecord>(&self, limit: usize) -> Result<Vec<E>> {
+        self.query_events(
+            "SELECT date, time, reason FROM (
+                 SELECT id, date, time, reason FROM events
+                 WHERE kind = ?1
+                 ORDER BY date DESC, id DESC LIMIT ?2
+             ) ORDER BY date, id",
+            params![E::KIND, limit],
+        )
+    }
+
+    /// Removes the whole history
+    pub fn clear(&self) -> Result<()> {
+        self.conn
+            .execute_batch("DELETE FROM phases; DELETE FROM events;")?;
+        Ok(())
+    }
+
+    fn query_phases(&self, sql: &str, params: &[&dyn ToSql]) -> Result<Vec<StatisticEntry>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let entries = stmt
+            .query_map(params, |row| {
+                Ok(StatisticEntry {
+                    start: row.get(0)?,
+                    end: row.get(1)?,
+                    is_wtime: row.get(2)?,
+                    is_long_break: row.get(3)?,
+                    planned: row.get(4)?,
+                    time: row.get(5)?,
+                    outcome: row.get(6)?,
+                    phase: row.get(7)?,
+                    profile: row.get(8)?,
+                    escalation: row.get(9)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(entries)
+    }
+
+    fn query_events<E: EventRecord>(&self, sql: &str, params: &[&dyn ToSql]) -> Result<Vec<E>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let entries = stmt
+            .query_map(params, |row| {
+                Ok(E::from_row(row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(entries)
+    }
+}
+
+fn insert_phase(conn: &Connection, entry: &StatisticEntry) -> Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO phases ({PHASE_COLUMNS})
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+        ),
+        params![
+            entry.start,
+            entry.end,
+            entry.is_wtime,
+            entry.is_long_break,
+            entry.planned,
+            entry.time,
+            entry.outcome,
+            entry.phase,
+            entry.profile,
+            entry.escalation,
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_events<E: EventRecord>(conn: &Connection, entries: &[E]) -> Result<()> {
+    let mut stmt = conn
+        .prepare_cached("INSERT INTO events (kind, date, time, reason) VALUES (?1, ?2, ?3, ?4)")?;
+    for entry in entries {
+        stmt.execute(params![E::KIND, entry.date(), entry.time(), entry.reason()])?;
+    }
+    Ok(())
+}
+
+impl ToSql for Time {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_secs().into())
+    }
+}
+
+impl FromSql for Time {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        u32::column_result(value).map(Self::from_secs)
+    }
+}
+
+/// Enums are stored by the names of their variants: the names don't change
+/// if new variants are added
+macro_rules! sql_enum {
+    ($enum:ident { $($variant:ident => $name:literal),+ $(,)? }) => {
+        impl ToSql for $enum {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                let name = match self {
+                    $(Self::$variant => $name,)+
+                };
+                Ok(name.into())
+            }
+        }
+
+        impl FromSql for $enum {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                match value.as_str()? {
+                    $($name => Ok(Self::$variant),)+
+                    other => Err(FromSqlError::Other(
+                        format!("Unknown {} '{other}'", stringify!($enum)).into(),
+                    )),
+                }
+            }
+        }
+    };
+}
+
+sql_enum!(EscalationLevel {
+    Notification => "notification",
+    Critical => "critical",
+    Window => "window",
+    Lock => "lock",
+});
+
+sql_enum!(PhaseOutcome {
+    Finished => "finished",
+    Skipped => "skipped",
+    Stopped => "stopped",
+    Postponed => "postponed",
+});
+
+sql_enum!(PauseReason {
+    Meeting => "meeting",
+    Lunch => "lunch",
+    Manual => "manual",
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::ALL_TIME;
+
+    fn store() -> StatsStore {
+        StatsStore::with_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn phase(end: u64, is_wtime: bool, is_long_break: bool) -> StatisticEntry {
+        StatisticEntry {
+            start: end - 60,
+            end,
+            is_wtime,
+            is_long_break,
+            planned: Some(Time::from_secs(60)),
+            time: Time::from_secs(60),
+            outcome: PhaseOutcome::Finished,
+            phase: String::new(),
+            profile: String::new(),
+            escalation: None,
+        }
+    }
+
+    fn ends(entries: &[StatisticEntry]) -> Vec<u64> {
+        entries.iter().map(|entry| entry.end).collect()
+    }
+
+    #[test]
+    fn migrate_first_version() {
+        // Схема версии 1: ни начала фазы, ни плана, ни итога
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "
+            CREATE TABLE phases (
+                id INTEGER PRIMARY KEY,
+                date INTEGER NOT NULL,
+                is_work INTEGER NOT NULL,
+                is_long_break INTEGER NOT NULL,
+                time INTEGER NOT NULL,
+                phase TEXT NOT NULL,
+                profile TEXT NOT NULL,
+                escalation TEXT
+            );
+            CREATE INDEX phases_date ON phases (date);
+            CREATE TABLE events (
+                id INTEGER PRIMARY KEY,
+                kind TEXT NOT NULL,
+                date INTEGER NOT NULL,
+                time INTEGER NOT NULL,
+                reason TEXT
+            );
+            CREATE INDEX events_kind_date ON events (kind, date);
+            CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+
+            INSERT INTO phases (date, is_work, is_long_break, time, phase, profile, escalation)
+            VALUES (5000, 1, 0, 1500, '', 'Deep work', NULL),
+                   (5300, 0, 0, 300, '', '', 'window'),
+                   (100, 1, 0, 600, '', '', NULL);
+            INSERT INTO events (kind, date, time, reason) VALUES ('idle', 4000, 120, NULL);
+            INSERT INTO meta (key, value) VALUES ('toml_imported', '1');
+            PRAGMA user_version = 1;
+            ",
+        )
+        .unwrap();
+
+        let store = StatsStore::with_connection(conn).unwrap();
+        let version: u32 = store
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        assert!(store.is_imported().unwrap());
+
+        let phases = store.phases(ALL_TIME, &[]).unwrap();
+        let migrated = phases
+            .iter()
+            .map(|entry| (entry.start, entry.end, entry.planned, entry.outcome))
+            .collect::<Vec<_>>();
+        // Начало - конец минус длительность, но не раньше нуля
+        assert_eq!(
+            migrated,
+            [
+                (0, 100, None, PhaseOutcome::Finished),
+                (3500, 5000, None, PhaseOutcome::Finished),
+                (5000, 5300, None, PhaseOutcome::Finished),
+            ]
+        );
+        assert_eq!(phases[1].profile, "Deep work");
+        assert_eq!(phases[2].escalation, Some(EscalationLevel::Window));
+        assert_eq!(store.events::<IdleEntry>(ALL_TIME).unwrap().len(), 1);
+
+        // Новые фазы пишутся уже по новой схеме
+        store.insert_phase(&phase(6000, true, false)).unwrap();
+        let last = store.recent_phases(1, ALL_TIME.end).unwrap();
+        assert_eq!(last[0].planned, Some(Time::from_secs(60)));
+    }
+
+    #[test]
+    fn newer_version_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(StatsStore::with_connection(conn).is_err());
+    }
+
+    #[test]
+    fn import_from_toml() {
+        let stats: Stats = toml::from_str(
+            r#"
+            version = 2
+
+            [[stats]]
+            start = 1000
+            end = 2500
+            is_wtime = true
+            planned = 1800
+            time = 1500
+            outcome = "Stopped"
+            profile = "Deep work"
+
+            [[stats]]
+            start = 2500
+            end = 2800
+            is_wtime = false
+            is_long_break = true
+            time = 300
+            outcome = "Skipped"
+            phase = "Stretch"
+            escalation = "Critical"
+
+            [[idle]]
+            date = 3000
+            time = 120
+
+            [[pauses]]
+            date = 4000
+            time = 600
+            reason = "Lunch"
+
+            [[postponed]]
+            date = 3500
+            time = 300
+            "#,
+        )
+        .unwrap();
+
+        let mut store = store();
+        assert!(!store.is_imported().unwrap());
+        store.import(&stats).unwrap();
+        assert!(store.is_imported().unwrap());
+
+        assert_eq!(store.phases(ALL_TIME, &[]).unwrap(), stats.stats);
+        assert_eq!(store.events::<IdleEntry>(ALL_TIME).unwrap(), stats.idle);
+        assert_eq!(store.events::<PauseEntry>(ALL_TIME).unwrap(), stats.pauses);
+        assert_eq!(
+            store.events::<PostponeEntry>(ALL_TIME).unwrap(),
+            stats.postponed
+        );
+        assert!(store.events::<DayEndEntry>(ALL_TIME).unwrap().is_empty());
+    }
+
+    #[test]
+    fn recent_phases_order_and_limit() {
+        let store = store();
+        // Вставляем не по порядку: порядок задаёт дата окончания
+        for end in [500, 100, 400, 200, 300] {
+            store.insert_phase(&phase(end, true, false)).unwrap();
+        }
+
+        assert_eq!(
+            ends(&store.recent_phases(2, ALL_TIME.end).unwrap()),
+            [400, 500]
+        );
+        assert_eq!(
+            ends(&store.recent_phases(2, 200).unwrap()),
+            [200, 300, 400, 500]
+        );
+        assert_eq!(ends(&store.recent_phases(0, 450).unwrap()), [500]);
+        assert_eq!(
+            ends(&store.recent_phases(10, ALL_TIME.end).unwrap()),
+            [100, 200, 300, 400, 500]
+        );
+    }
+
+    #[test]
+    fn phases_by_kind_and_range() {
+        let store = store();
+        store.insert_phase(&phase(100, true, false)).unwrap();
+        store.insert_phase(&phase(200, false, false)).unwrap();
+        store.insert_phase(&phase(300, true, false)).unwrap();
+        store.insert_phase(&phase(400, false, true)).unwrap();
+
+        let kinds = |kinds: &[PhaseKind]| ends(&store.phases(ALL_TIME, kinds).unwrap());
+        assert_eq!(kinds(&[]), [100, 200, 300, 400]);
+        assert_eq!(kinds(&[PhaseKind::Work]), [100, 300]);
+        assert_eq!(kinds(&[PhaseKind::Break]), [200]);
+        assert_eq!(kinds(&[PhaseKind::LongBreak]), [400]);
+        assert_eq!(kinds(&[PhaseKind::Break, PhaseKind::LongBreak]), [200, 400]);
+
+        // Конец диапазона не включается
+        let range = store.phases(200..400, &[]).unwrap();
+        assert_eq!(ends(&range), [200, 300]);
+    }
+
+    #[test]
+    fn clear_removes_history() {
+        let mut store = store();
+        store.import(&Stats::default()).unwrap();
+        store.insert_phase(&phase(100, true, false)).unwrap();
+        store
+            .insert_event(&IdleEntry {
+                date: 100,
+                time: Time::from_secs(60),
+            })
+            .unwrap();
+
+        store.clear().unwrap();
+        assert!(store.phases(ALL_TIME, &[]).unwrap().is_empty());
+        assert!(store.events::<IdleEntry>(ALL_TIME).unwrap().is_empty());
+        // Импорт не повторяется: старые записи не должны вернуться
+        assert!(store.is_imported().unwrap());
+    }
+
+    #[test]
+    fn enums_by_name() {
+        let store = store();
+        let outcomes = [
+            PhaseOutcome::Finished,
+            PhaseOutcome::Skipped,
+            PhaseOutcome::Stopped,
+            PhaseOutcome::Postponed,
+        ];
+        let levels = [
+            EscalationLevel::Notification,
+            EscalationLevel::Critical,
+            EscalationLevel::Window,
+            EscalationLevel::Lock,
+        ];
+        for (i, (outcome, level)) in outcomes.into_iter().zip(levels).enumerate() {
+            let mut entry = phase(100 + i as u64, false, false);
+            entry.outcome = outcome;
+            entry.escalation = Some(level);
+            store.insert_phase(&entry).unwrap();
+        }
+        for reason in [
+            PauseReason::Meeting,
+            PauseReason::Lunch,
+            PauseReason::Manual,
+        ] {
+            let entry = PauseEntry {
+                date: 100,
+                time: Time::from_secs(60),
+                reason,
+            };
+            store.insert_event(&entry).unwrap();
+        }
+
+        let phases = store.phases(ALL_TIME, &[]).unwrap();
+        let read = phases.iter().map(|entry| (entry.outcome, entry.escalation));
+        assert!(read.eq(outcomes.into_iter().zip(levels.map(Some))));
+        let pauses = store.events::<PauseEntry>(ALL_TIME).unwrap();
+        let reasons = pauses.iter().map(|entry| entry.reason).collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            [
+                PauseReason::Meeting,
+                PauseReason::Lunch,
+                PauseReason::Manual
+            ]
+        );
+
+        // Неизвестное имя - ошибка, а не вариант по умолчанию
+        store
+            .conn
+            .execute("UPDATE phases SET outcome = 'lost'", [])
+            .unwrap();
+        assert!(store.phases(ALL_TIME, &[]).is_err());
+    }
+}
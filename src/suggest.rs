@@ -0,0 +1,313 @@
+//! Adaptive interval suggestions
+//!
+//! The statistics show how the user really works: how often breaks are
+//! postponed, cut short or skipped and how long the phases actually last.
+//! [`suggest`] turns this into new work/break durations for the Preferences
+//! page, each backed by a [`Reason`]. With [`AutoAdjust`] the suggestion is
+//! applied once a week on its own, within the bounds set by the user.
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    conf::Config,
+    consts::{SUGGEST_BREAK_RANGE, SUGGEST_MIN_PHASES, SUGGEST_PERIOD, SUGGEST_WORK_RANGE},
+    fl,
+    stats::{ALL_TIME, PostponeEntry, Stats},
+    time::{Time, TimeStyle, get_current_date},
+    timer::PhaseOutcome,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AutoAdjust {
+    /// Apply the suggestion automatically once a week
+    pub enabled: bool,
+
+    /// Work time is never adjusted below this value
+    pub min_work_time: Time,
+
+    /// Work time is never adjusted above this value
+    pub max_work_time: Time,
+
+    /// Free time is never adjusted below this value
+    pub min_free_time: Time,
+
+    /// Free time is never adjusted above this value
+    pub max_free_time: Time,
+
+    /// Date&time of the last adjustment in UNIX Timestamp format
+    pub last_adjusted: u64,
+}
+
+impl Default for AutoAdjust {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_work_time: Time::from_mins(30), // От получаса
+            max_work_time: Time::from_mins(90), // до полутора часов работы
+            min_free_time: Time::from_mins(5),
+            max_free_time: Time::from_mins(20),
+            last_adjusted: 0,
+        }
+    }
+}
+
+impl AutoAdjust {
+    /// Keeps the suggested durations within the user bounds
+    pub fn clamp(&self, suggestion: &mut Suggestion) {
+        suggestion.work_time = suggestion.work_time.clamp(
+            self.min_work_time,
+            self.max_work_time.max(self.min_work_time),
+        );
+        suggestion.free_time = suggestion.free_time.clamp(
+            self.min_free_time,
+            self.max_free_time.max(self.min_free_time),
+        );
+    }
+}
+
+/// New work/break durations and why they are suggested
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub work_time: Time,
+
+    pub free_time: Time,
+
+    pub reasons: Vec<Reason>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    /// `count` of the last `phases` work phases ended with a postponed break
+    Postponed { count: usize, phases: usize },
+    /// Work phases usually last `median` instead of the set time
+    WorkLength { median: Time },
+    /// `count` of the last `breaks` breaks were cut short or skipped. They
+    /// usually last `median`
+    BreaksCut {
+        count: usize,
+        breaks: usize,
+        median: Time,
+    },
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match *self {
+            Self::Postponed { count, phases } => {
+                fl!("suggest_postponed", count = count, phases = phases)
+            }
+            Self::WorkLength { median } => {
+                let median = median.format(TimeStyle::Human);
+                fl!("suggest_work_length", median = median)
+            }
+            Self::BreaksCut {
+                count,
+                breaks,
+                median,
+            } => {
+                let median = median.format(TimeStyle::Human);
+                fl!(
+                    "suggest_breaks_cut",
+                    count = count,
+                    breaks = breaks,
+                    median = median
+                )
+            }
+        };
+        write!(f, "{msg}")
+    }
+}
+
+/// Suggests the work/break durations for the intervals from the settings
+/// by the phases of the last [`SUGGEST_PERIOD`]. Phases of profiles and
+/// phase sequences are not taken into account, nor are work phases not
+/// longer than `snooze_time`. `None` - there is not enough history or the
+/// current durations fit
+pub fn suggest(stats: &Stats, conf: &Config) -> Option<Suggestion> {
+    let range = get_current_date().saturating_sub(SUGGEST_PERIOD)..ALL_TIME.end;
+    let phases = stats.phases(range.clone(), &[]);
+    // Остановленные и отложенные фазы оборваны не по желанию пользователя
+    // отдохнуть или поработать - они ничего не говорят о длине интервалов
+    let entries = phases.iter().filter(|entry| {
+        entry.phase.is_empty()
+            && entry.profile.is_empty()
+            && !entry.is_long_break
+            && matches!(
+                entry.outcome,
+                PhaseOutcome::Finished | PhaseOutcome::Skipped
+            )
+    });
+    // Работа после отложенного перерыва длится snooze_time и записывается
+    // отдельной фазой - она не показывает, сколько длится обычная работа.
+    // Такая фаза заканчивается так же, как обычная работа, и отличить её
+    // можно только по длительности. Поэтому отбрасываются все рабочие фазы
+    // не длиннее snooze_time: рабочий интервал в настройках не короче
+    // получаса, и столь короткая работа - это почти всегда доработка после
+    // отложенного перерыва, которая занизила бы медиану
+    let work = entries
+        .clone()
+        .filter(|entry| entry.is_wtime && entry.time > conf.snooze_time)
+        .map(|entry| entry.time)
+        .collect::<Vec<_>>();
+    let breaks = entries
+        .clone()
+        .filter(|entry| !entry.is_wtime)
+        .map(|entry| entry.time)
+        .collect::<Vec<_>>();
+    let since = entries.map(|entry| entry.end).min()?;
+
+    let mut suggestion = Suggestion {
+        work_time: conf.work_time,
+        free_time: conf.free_time,
+        reasons: Vec::new(),
+    };
+
+    if work.len() >= SUGGEST_MIN_PHASES {
+        let postponed = stats.events::<PostponeEntry>(since..range.end).len();
+        let median = median(&work);
+
+        // Перерыв откладывают в каждом втором цикле - работы не хватает
+        if postponed * 2 >= work.len() {
+            suggestion.work_time = conf.work_time + conf.snooze_time;
+            suggestion.reasons.push(Reason::Postponed {
+                count: postponed,
+                phases: work.len(),
+            });
+        } else if differs(median, conf.work_time) {
+            suggestion.work_time = median;
+            suggestion.reasons.push(Reason::WorkLength { median });
+        }
+    }
+
+    if breaks.len() >= SUGGEST_MIN_PHASES {
+        let cut = breaks
+            .iter()
+            .filter(|&&time| time.to_secs() as u64 * 10 < conf.free_time.to_secs() as u64 * 9)
+            .count();
+        let median = median(&breaks);
+
+        // Короткий перерыв, который действительно соблюдают, полезнее
+        // длинного, который обрывают
+        if cut * 2 >= breaks.len() && differs(median, conf.free_time) {
+            suggestion.free_time = median;
+            suggestion.reasons.push(Reason::BreaksCut {
+                count: cut,
+                breaks: breaks.len(),
+                median,
+            });
+        }
+    }
+
+    if suggestion.reasons.is_empty() {
+        return None;
+    }
+    suggestion.work_time = round_to_mins(suggestion.work_time, SUGGEST_WORK_RANGE);
+    suggestion.free_time = round_to_mins(suggestion.free_time, SUGGEST_BREAK_RANGE);
+
+    match suggestion.work_time != conf.work_time || suggestion.free_time != conf.free_time {
+        true => Some(suggestion),
+        false => None,
+    }
+}
+
+fn median(times: &[Time]) -> Time {
+    let mut times = times.to_vec();
+    times.sort_unstable();
+    times[times.len() / 2]
+}
+
+/// Does the real length differ from the set one by more than 10%?
+fn differs(real: Time, set: Time) -> bool {
+    let diff = real.max(set) - real.min(set);
+    diff.to_secs() as u64 * 10 > set.to_secs() as u64
+}
+
+/// Rounds to whole minutes within `range` (in seconds)
+fn round_to_mins(time: Time, range: RangeInclusive<u32>) -> Time {
+    let secs = time.to_secs().saturating_add(30) / 60 * 60;
+    Time::from_secs(secs.clamp(*range.start(), *range.end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::StatisticEntry;
+
+    /// A break of the intervals from the settings which ended `ago` seconds
+    /// ago
+    fn short_break(ago: u64) -> StatisticEntry {
+        let end = get_current_date() - ago;
+        StatisticEntry {
+            start: end - 300,
+            end,
+            is_wtime: false,
+            is_long_break: false,
+            planned: Some(Time::from_mins(15)),
+            time: Time::from_mins(5),
+            outcome: PhaseOutcome::Skipped,
+            phase: String::new(),
+            profile: String::new(),
+            escalation: None,
+        }
+    }
+
+    /// A work phase of the intervals from the settings which lasted `mins`
+    /// and ended `ago` seconds ago
+    fn work(ago: u64, mins: u32) -> StatisticEntry {
+        let time = Time::from_mins(mins);
+        StatisticEntry {
+            start: get_current_date() - ago - time.to_secs() as u64,
+            is_wtime: true,
+            planned: Some(Time::from_mins(60)),
+            time,
+            outcome: PhaseOutcome::Finished,
+            ..short_break(ago)
+        }
+    }
+
+    #[test]
+    fn snooze_work_ignored() {
+        let conf = Config::default();
+        let mut stats = Stats::default();
+        // Доработка после отложенного перерыва (без записи об откладывании
+        // она не отличается от обычной работы)
+        for hour in 1..5 {
+            stats.push(work(hour * 3600, conf.snooze_time.to_secs() / 60));
+        }
+        assert_eq!(suggest(&stats, &conf), None);
+
+        // Медиана - по настоящей работе, доработка её не занижает
+        for hour in 5..8 {
+            stats.push(work(hour * 3600, 40));
+        }
+        let suggestion = suggest(&stats, &conf).unwrap();
+        assert_eq!(suggestion.work_time, Time::from_mins(40));
+        assert_eq!(
+            suggestion.reasons,
+            [Reason::WorkLength {
+                median: Time::from_mins(40)
+            }]
+        );
+    }
+
+    #[test]
+    fn only_recent_phases() {
+        let conf = Config::default();
+        let mut stats = Stats::default();
+        for day in 8..12 {
+            stats.push(short_break(day * 24 * 3600));
+        }
+        // Обрывали перерывы больше недели назад - это уже не в счёт
+        assert_eq!(suggest(&stats, &conf), None);
+
+        for hour in 1..4 {
+            stats.push(short_break(hour * 3600));
+        }
+        let suggestion = suggest(&stats, &conf).unwrap();
+        assert_eq!(suggestion.free_time, Time::from_mins(5));
+        assert_eq!(suggestion.work_time, conf.work_time);
+    }
+}
@@ -1,18 +1,24 @@
-//! Converting seconds to [`Time`] and [`Time`] to seconds
+//! Durations ([`Time`]) and dates in TimeKeeper
 
 use std::{
     fmt::Display,
+    iter::Sum,
+    ops::{Add, AddAssign, Sub, SubAssign},
+    str::FromStr,
     time::{Duration, SystemTime},
 };
 
 use anyhow::{Result, anyhow};
 use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::fl;
 
 pub fn fmt_date(s: u64) -> String {
     let dt = DateTime::from_timestamp(s as i64, 0);
 
     match dt {
-        None => format!("Неизвестное время"), // TODO: replace this
+        None => "Неизвестное время".to_string(), // TODO: replace this
         Some(dt) => dt.format("%d.%m %H:%M").to_string(),
     }
 }
@@ -24,114 +30,372 @@ pub fn get_current_date() -> u64 {
     sys_time.as_secs()
 }
 
-/// Time representation in `TimeKeeper`
-///
-/// ## Value ranges
-/// - Hours: [0; 3]
-/// - Minutes: [0; 59]
-/// - Seconds: [0; 59]
+/// Non-negative duration with second precision
 ///
-/// ## Max values
-/// - Hours: 3
-/// - Minutes: 60 (panics if >= 60)
-/// - Seconds: 60 (panics if >= 60)
-#[derive(Debug, Clone, Copy)]
+/// - Arithmetic never panics: `+` and `-` saturate at [`Time::MAX`] and
+///   [`Time::ZERO`], `checked_*` methods return `None` instead.
+/// - Parsed from strings like `"1h30m"`, `"90m"`, `"45s"`, `"1:30:00"` or
+///   `"5400"` (seconds).
+/// - Deserialized from such a string or from a number of seconds, serialized
+///   as a number of seconds.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+#[serde(try_from = "TimeRepr", into = "u32")]
 pub struct Time {
-    pub hours: u8,
-    pub mins: u8,
-    pub secs: u8,
+    secs: u32,
 }
 
-impl Default for Time {
-    fn default() -> Self {
-        Self {
-            hours: 0,
-            mins: 0,
-            secs: 0,
-        }
-    }
+/// How [`Time`] is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeStyle {
+    /// `1:30:00`
+    Clock,
+    /// `1:30`
+    ClockWithoutSecs,
+    /// `1h 30m`, `45s` with the units in the user's language
+    Human,
 }
 
 impl Time {
-    pub fn new(h: u8, m: u8, s: u8) -> Self {
-        if h > 3 && m > 0 && s > 0 {
-            // https://www.aoa.org/healthy-eyes/eye-and-vision-conditions/computer-vision-syndrome?sso=y
-            panic!("Value `h` is out of range (max: 3, given: {h})")
-        } else if m >= 60 {
-            panic!("Value `m` is out of range (max: 59, given: {m})")
-        } else if s >= 60 {
-            panic!("Value `s` is out of range (max: 59, given: {s})")
-        } else {
-            Self {
-                hours: h,
-                mins: m,
-                secs: s,
-            }
-        }
-    }
+    pub const ZERO: Self = Self { secs: 0 };
+    pub const MAX: Self = Self { secs: u32::MAX };
 
-    pub fn try_new(h: u8, m: u8, s: u8) -> Result<Self> {
-        if h > 3 && m > 0 && s > 0 {
-            return Err(anyhow!("Value `h` is out of range (max: 3, given: {h})"));
-        } else if m >= 60 {
+    /// Creates a new instance of `Time` from hours, minutes and seconds.
+    /// Minutes and seconds must be less than 60
+    pub fn try_new(h: u32, m: u32, s: u32) -> Result<Self> {
+        if m >= 60 {
             return Err(anyhow!("Value `m` is out of range (max: 59, given: {m})"));
         } else if s >= 60 {
-            return Err(anyhow!("Value `s` is out of range (max: 59, given: {s}"));
+            return Err(anyhow!("Value `s` is out of range (max: 59, given: {s})"));
         }
 
-        Ok(Self {
-            hours: h,
-            mins: m,
-            secs: s,
-        })
+        h.checked_mul(3600)
+            .and_then(|secs| secs.checked_add(m * 60 + s))
+            .map(Self::from_secs)
+            .ok_or_else(|| anyhow!("Value `h` is out of range (given: {h})"))
     }
 
     /// Creates a new instance of `Time` from seconds
-    pub fn from_secs(s: u16) -> Self {
-        let hours: u8 = (s / 3600) as u8;
-        let minutes: u8 = ((s as u16 - 3600 * hours as u16) / 60) as u8;
-        let s: u8 = (s as u16 - (3600 * hours as u16) - 60 * minutes as u16) as u8;
+    pub const fn from_secs(secs: u32) -> Self {
+        Self { secs }
+    }
 
-        Self::new(hours, minutes, s)
+    pub const fn from_mins(mins: u32) -> Self {
+        Self {
+            secs: mins.saturating_mul(60),
+        }
     }
 
-    pub fn try_from_secs(s: u16) -> Result<Self> {
-        let hours: u8 = (s / 3600) as u8;
-        let minutes: u8 = ((s as u16 - 3600 * hours as u16) / 60) as u8;
-        let s: u8 = (s as u16 - (3600 * hours as u16) - 60 * minutes as u16) as u8;
+    /// Whole seconds of `duration` (saturating at [`Time::MAX`])
+    pub fn from_duration(duration: Duration) -> Self {
+        Self::from_secs(duration.as_secs().min(u32::MAX as u64) as u32)
+    }
 
-        Self::try_new(hours, minutes, s)
+    /// Like [`Time::from_duration`], but a fraction of a second counts as a
+    /// whole second
+    pub fn from_duration_ceil(duration: Duration) -> Self {
+        let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+        Self::from_secs(secs.min(u32::MAX as u64) as u32)
     }
 
     /// Convert [`Time`] to seconds
-    pub fn to_secs(&self) -> u16 {
-        let mut s: u16 = 3600 * self.hours as u16; // hours to seconds
-        s += 60 * self.mins as u16; // minutes to seconds
-        s += self.secs as u16;
+    pub const fn to_secs(self) -> u32 {
+        self.secs
+    }
 
-        s
+    pub const fn to_duration(self) -> Duration {
+        Duration::from_secs(self.secs as u64)
     }
 
-    pub fn to_string_without_secs(&self) -> String {
-        format!(
-            "{}:{}{}",
-            self.hours,
-            if self.mins < 10 { "0" } else { "" },
-            self.mins
-        )
+    pub const fn is_zero(self) -> bool {
+        self.secs == 0
+    }
+
+    pub const fn hours(self) -> u32 {
+        self.secs / 3600
+    }
+
+    /// Minutes of the hour: [0; 59]
+    pub const fn mins(self) -> u32 {
+        self.secs % 3600 / 60
+    }
+
+    /// Seconds of the minute: [0; 59]
+    pub const fn secs(self) -> u32 {
+        self.secs % 60
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.secs.checked_add(rhs.secs).map(Self::from_secs)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.secs.checked_sub(rhs.secs).map(Self::from_secs)
+    }
+
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self::from_secs(self.secs.saturating_add(rhs.secs))
+    }
+
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self::from_secs(self.secs.saturating_sub(rhs.secs))
+    }
+
+    pub fn format(self, style: TimeStyle) -> String {
+        let (h, m, s) = (self.hours(), self.mins(), self.secs());
+        match style {
+            TimeStyle::Clock => format!("{h}:{m:02}:{s:02}"),
+            TimeStyle::ClockWithoutSecs => format!("{h}:{m:02}"),
+            TimeStyle::Human => {
+                let mut parts = Vec::new();
+                if h > 0 {
+                    parts.push(fl!("time_hours", value = h));
+                }
+                if m > 0 {
+                    parts.push(fl!("time_mins", value = m));
+                }
+                if s > 0 || parts.is_empty() {
+                    parts.push(fl!("time_secs", value = s));
+                }
+                parts.join(" ")
+            }
+        }
+    }
+
+    pub fn to_string_without_secs(self) -> String {
+        self.format(TimeStyle::ClockWithoutSecs)
     }
 }
 
 impl Display for Time {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}:{}{}:{}{}",
-            self.hours,
-            if self.mins < 10 { "0" } else { "" },
-            self.mins,
-            if self.secs < 10 { "0" } else { "" },
-            self.secs
-        )
+        write!(f, "{}", self.format(TimeStyle::Clock))
+    }
+}
+
+impl FromStr for Time {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(anyhow!("Time must not be empty"));
+        }
+
+        // Просто число - это секунды
+        if s.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(Self::from_secs(s.parse()?));
+        }
+
+        // "H:MM" или "H:MM:SS"
+        if s.contains(':') {
+            let parts = s
+                .split(':')
+                .map(|part| part.trim().parse::<u32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| anyhow!("Time '{s}' must be in the 'H:MM[:SS]' format"))?;
+            return match parts[..] {
+                [h, m] => Self::try_new(h, m, 0),
+                [h, m, s] => Self::try_new(h, m, s),
+                _ => Err(anyhow!("Time '{s}' must be in the 'H:MM[:SS]' format")),
+            };
+        }
+
+        // "1h30m", "90m", "45s", "1h 30m 15s"
+        let mut total = Self::ZERO;
+        let mut number = String::new();
+        for c in s.chars() {
+            match c {
+                '0'..='9' => number.push(c),
+                'h' | 'm' | 's' => {
+                    let value = number
+                        .parse::<u32>()
+                        .map_err(|_| anyhow!("Missing a number before '{c}' in '{s}'"))?;
+                    let secs = match c {
+                        'h' => value.checked_mul(3600),
+                        'm' => value.checked_mul(60),
+                        _ => Some(value),
+                    };
+                    total = secs
+                        .and_then(|secs| total.checked_add(Self::from_secs(secs)))
+                        .ok_or_else(|| anyhow!("Time '{s}' is too long"))?;
+                    number.clear();
+                }
+                c if c.is_whitespace() => {}
+                c => return Err(anyhow!("Unexpected '{c}' in time '{s}'")),
+            }
+        }
+        if !number.is_empty() {
+            return Err(anyhow!(
+                "Missing a unit (h, m or s) after '{number}' in '{s}'"
+            ));
+        }
+
+        Ok(total)
+    }
+}
+
+/// Both forms of [`Time`] in the config and statistics files
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TimeRepr {
+    Secs(u64),
+    Text(String),
+}
+
+impl TryFrom<TimeRepr> for Time {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TimeRepr) -> Result<Self> {
+        match value {
+            TimeRepr::Secs(secs) => u32::try_from(secs)
+                .map(Self::from_secs)
+                .map_err(|_| anyhow!("Time {secs} s is too long")),
+            TimeRepr::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<Time> for u32 {
+    fn from(value: Time) -> Self {
+        value.secs
+    }
+}
+
+impl From<Duration> for Time {
+    fn from(value: Duration) -> Self {
+        Self::from_duration(value)
+    }
+}
+
+impl Add for Time {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.saturating_add(rhs)
+    }
+}
+
+impl Sub for Time {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.saturating_sub(rhs)
+    }
+}
+
+impl AddAssign for Time {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Time {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sum for Time {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Conf {
+        work_time: Time,
+    }
+
+    fn parse(s: &str) -> Time {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_units() {
+        assert_eq!(parse("1h30m"), Time::from_secs(5400));
+        assert_eq!(parse("1h 30m 15s"), Time::from_secs(5415));
+        assert_eq!(parse("90m"), Time::from_secs(5400));
+        assert_eq!(parse("45s"), Time::from_secs(45));
+    }
+
+    #[test]
+    fn parse_clock() {
+        assert_eq!(parse("1:30:00"), Time::from_secs(5400));
+        assert_eq!(parse("1:30"), Time::from_secs(5400));
+        assert_eq!(parse("0:00:45"), Time::from_secs(45));
+    }
+
+    #[test]
+    fn parse_secs() {
+        assert_eq!(parse("5400"), Time::from_secs(5400));
+        assert_eq!(parse(" 0 "), Time::ZERO);
+    }
+
+    #[test]
+    fn parse_errors() {
+        for s in [
+            "", " ", "5x", "1h30", "h", "1:75", "1:30:60", "1:", "1:2:3:4",
+        ] {
+            assert!(s.parse::<Time>().is_err(), "'{s}' must not be parsed");
+        }
+    }
+
+    #[test]
+    fn parse_overflow() {
+        assert!("4294967296".parse::<Time>().is_err());
+        assert!("1193047h".parse::<Time>().is_err());
+        assert!("71582789m".parse::<Time>().is_err());
+        assert!("1193046h 60m".parse::<Time>().is_err());
+        assert!("1193047:00".parse::<Time>().is_err());
+        assert_eq!(parse("4294967295"), Time::MAX);
+    }
+
+    #[test]
+    fn deserialize_both_forms() {
+        let conf: Conf = toml::from_str("work_time = 3600").unwrap();
+        assert_eq!(conf.work_time, Time::from_secs(3600));
+
+        let conf: Conf = toml::from_str("work_time = \"1h\"").unwrap();
+        assert_eq!(conf.work_time, Time::from_secs(3600));
+
+        assert!(toml::from_str::<Conf>("work_time = 4294967296").is_err());
+        assert!(toml::from_str::<Conf>("work_time = -1").is_err());
+        assert!(toml::from_str::<Conf>("work_time = \"1x\"").is_err());
+    }
+
+    #[test]
+    fn serialize_as_secs() {
+        #[derive(Serialize)]
+        struct Conf {
+            work_time: Time,
+        }
+
+        let conf = Conf {
+            work_time: Time::from_mins(90),
+        };
+        assert_eq!(toml::to_string(&conf).unwrap().trim(), "work_time = 5400");
+    }
+
+    #[test]
+    fn arithmetic_saturates() {
+        let (a, b) = (Time::from_secs(10), Time::from_secs(30));
+        assert_eq!(a - b, Time::ZERO);
+        assert_eq!(b - a, Time::from_secs(20));
+        assert_eq!(Time::MAX + a, Time::MAX);
+        assert_eq!(a.checked_sub(b), None);
+        assert_eq!(Time::MAX.checked_add(a), None);
+
+        let mut t = a;
+        t -= b;
+        assert_eq!(t, Time::ZERO);
+        t += b;
+        assert_eq!(t, b);
+        assert_eq!([a, b, a].into_iter().sum::<Time>(), Time::from_secs(50));
     }
 }
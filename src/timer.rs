@@ -0,0 +1,1848 @@
+//! Headless work/break timer engine
+//!
+//! The engine knows nothing about windows, notifications or statistics. It
+//! takes [`TimerCommand`]s and reports what happened as a list of
+//! [`TimerEvent`]s, so the UI, notifications and statistics can react to the
+//! cycle without being mixed into it.
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{conf::Config, fl, sequence::Phase, time::Time, traits::Toml};
+
+/// Commands accepted by the [`Timer`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimerCommand {
+    /// Start (or resume) counting the elapsed time
+    Start,
+    /// Pause counting the elapsed time
+    Pause,
+    /// Pause counting the elapsed time for the given time. Then the timer
+    /// resumes on its own
+    PauseFor(Time),
+    /// Reset the elapsed time, switch to the work phase and pause the timer
+    Stop,
+    /// Compare the wall clock with the phase deadline. Ticks only refresh the
+    /// state; they are not counted
+    Tick,
+    /// The user was away from the PC (the system slept or the session was
+    /// locked) for the given time. If it is not shorter than a break, it is
+    /// counted as one and a new work phase begins. A shorter absence during a
+    /// break is counted in the break
+    Away(Duration),
+    /// Postpone the current break: work for the snooze time more, then the
+    /// same break begins again
+    Postpone,
+    /// End the current phase right now and begin the next one ("take a break
+    /// now" or "end the break and resume work")
+    Skip,
+    /// Make the current phase longer by the given time
+    Extend(Time),
+}
+
+/// Events emitted by the [`Timer`] in response to a [`TimerCommand`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimerEvent {
+    /// A new phase has begun. `phase` is its index in the phase sequence
+    PhaseStarted {
+        is_work: bool,
+        is_long_break: bool,
+        phase: Option<usize>,
+    },
+    /// The phase is over. `elapsed` is its actual duration, `planned` is
+    /// the duration it should have lasted
+    PhaseFinished {
+        is_work: bool,
+        is_long_break: bool,
+        phase: Option<usize>,
+        elapsed: Time,
+        planned: Time,
+        outcome: PhaseOutcome,
+    },
+    /// The last phase of a non-repeating sequence is over. The timer is
+    /// stopped at the beginning of the sequence
+    SequenceFinished,
+    /// The timer was paused
+    Paused,
+    /// The timer was resumed after a pause
+    Resumed,
+    /// The timed pause is over and the timer has resumed on its own
+    PauseExpired,
+    /// The timer was reset to the beginning of the work phase
+    Stopped,
+    /// The user was away long enough for a break. `elapsed` is the time away
+    NaturalBreak { elapsed: Time },
+    /// The break was postponed by `by`. `snoozes_left` is how many more times
+    /// it can be postponed in this cycle
+    Postponed { by: Time, snoozes_left: u8 },
+    /// The current phase was made longer by `by`
+    Extended { by: Time },
+    /// The break begins in `left`. `is_final` is set for the last warning
+    /// before the break
+    BreakWarning { is_final: bool, left: Time },
+}
+
+/// How a phase ended
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhaseOutcome {
+    /// The phase lasted as long as planned
+    #[default]
+    Finished,
+    /// The user ended the phase early ("take a break now", "end the break")
+    Skipped,
+    /// The timer was stopped in the middle of the phase
+    Stopped,
+    /// The break was postponed
+    Postponed,
+}
+
+impl Display for PhaseOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Finished => fl!("outcome_finished"),
+            Self::Skipped => fl!("outcome_skipped"),
+            Self::Stopped => fl!("outcome_stopped"),
+            Self::Postponed => fl!("outcome_postponed"),
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// What happens to the running phase when the intervals change
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalPolicy {
+    /// The new durations apply right away. The elapsed time is kept, so a
+    /// phase that is already longer than its new duration ends now
+    #[default]
+    ApplyNow,
+    /// The new durations apply right away. The elapsed time is scaled, so the
+    /// same part of the phase is left
+    Scale,
+    /// The new durations apply when the next work phase begins
+    NextCycle,
+}
+
+impl IntervalPolicy {
+    pub const ALL: [Self; 3] = [Self::ApplyNow, Self::Scale, Self::NextCycle];
+}
+
+impl Display for IntervalPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ApplyNow => fl!("policy_apply_now"),
+            Self::Scale => fl!("policy_scale"),
+            Self::NextCycle => fl!("policy_next_cycle"),
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Saved state of the [`Timer`]. It is written to the data directory, so the
+/// running phase survives a restart or a crash of the program
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TimerState {
+    /// Date&time the state was saved in UNIX Timestamp format
+    pub saved_at: u64,
+
+    pub is_work: bool,
+    pub is_pause: bool,
+
+    /// Time counted in the current phase
+    pub elapsed: Time,
+
+    pub cycle: u8,
+    pub snoozes: u8,
+    pub is_postponed: bool,
+    pub extra_time: Time,
+
+    /// Index of the current phase in the phase sequence
+    pub step: usize,
+
+    /// Time left until the end of the timed pause
+    #[serde(default)]
+    pub pause_left: Option<Time>,
+}
+
+impl Toml for TimerState {}
+
+#[derive(Debug, Clone)]
+pub struct Timer {
+    /// Flag indicating whether the user is currently working or not
+    is_work: bool,
+
+    /// Time counted in the current phase
+    stopwatch: Stopwatch,
+
+    work_time: Time,
+
+    free_time: Time,
+
+    long_break_time: Time,
+
+    /// A long break follows every `long_break_every` work cycles. `0`
+    /// disables long breaks
+    long_break_every: u8,
+
+    /// Number of the current work cycle: [1; long_break_every]
+    cycle: u8,
+
+    snooze_time: Time,
+
+    /// How many times a break can be postponed in one cycle
+    max_snoozes: u8,
+
+    /// How many times the break of the current cycle was postponed
+    snoozes: u8,
+
+    /// Set while the user works extra time after postponing a break
+    is_postponed: bool,
+
+    /// Time added to the current phase by the user
+    extra_time: Time,
+
+    /// How long before a break the early and the final warnings are sent.
+    /// Zero disables a warning
+    warnings: [Time; 2],
+
+    /// Which warnings have already been sent in the current phase
+    warned: [bool; 2],
+
+    /// User-defined phases. Empty - work and breaks alternate
+    sequence: Vec<Phase>,
+
+    /// Does the sequence start again after the last phase?
+    repeat: bool,
+
+    /// Index of the current phase in the sequence
+    step: usize,
+
+    /// The moment the timed pause ends. `None` if the timer is running or
+    /// paused until the user resumes it
+    pause_until: Option<Instant>,
+
+    /// Intervals that apply when the next work phase begins
+    pending: Option<Config>,
+}
+
+impl Timer {
+    pub fn new(conf: &Config, now: Instant) -> Self {
+        Self {
+            is_work: first_is_work(&conf.sequence.phases),
+            stopwatch: Stopwatch::started(now),
+            work_time: conf.work_time,
+            free_time: conf.free_time,
+            long_break_time: conf.long_break_time,
+            long_break_every: conf.long_break_every,
+            cycle: 1,
+            snooze_time: conf.snooze_time,
+            max_snoozes: conf.max_snoozes,
+            snoozes: 0,
+            is_postponed: false,
+            extra_time: Time::ZERO,
+            warnings: [conf.break_warning, conf.break_final_warning],
+            warned: [false; 2],
+            sequence: conf.sequence.phases.clone(),
+            repeat: conf.sequence.repeat,
+            step: 0,
+            pause_until: None,
+            pending: None,
+        }
+    }
+
+    /// Change the phase durations. What happens to the running phase
+    /// depends on [`Config::interval_policy`]
+    pub fn set_intervals(&mut self, conf: &Config, now: Instant) {
+        let elapsed = self.stopwatch.elapsed(now);
+        let old_len = self.phase_duration();
+
+        match conf.interval_policy {
+            // Если фаза ещё не началась, откладывать нечего
+            IntervalPolicy::NextCycle if !elapsed.is_zero() => {
+                self.pending = Some(conf.clone());
+                return;
+            }
+            _ => self.apply_intervals(conf),
+        }
+
+        let new_len = self.phase_duration();
+        let elapsed = match conf.interval_policy {
+            IntervalPolicy::Scale if !old_len.is_zero() => {
+                elapsed.mul_f64(new_len.as_secs_f64() / old_len.as_secs_f64())
+            }
+            // Фаза, которая уже длиннее новой длительности, заканчивается
+            // сейчас, а не задним числом
+            _ => elapsed.min(new_len),
+        };
+        self.stopwatch.set_elapsed(elapsed, now);
+    }
+
+    /// Applies the intervals put off by [`IntervalPolicy::NextCycle`]
+    fn apply_pending(&mut self) {
+        if let Some(conf) = self.pending.take() {
+            self.apply_intervals(&conf);
+        }
+    }
+
+    fn apply_intervals(&mut self, conf: &Config) {
+        self.pending = None;
+        self.work_time = conf.work_time;
+        self.free_time = conf.free_time;
+        self.long_break_time = conf.long_break_time;
+        self.long_break_every = conf.long_break_every;
+        self.cycle = self.cycle.clamp(1, self.long_break_every.max(1));
+        self.snooze_time = conf.snooze_time;
+        self.max_snoozes = conf.max_snoozes;
+        self.warnings = [conf.break_warning, conf.break_final_warning];
+
+        // Новая последовательность начинается с первой фазы
+        if self.sequence != conf.sequence.phases {
+            self.sequence = conf.sequence.phases.clone();
+            self.step = 0;
+            if !self.sequence.is_empty() {
+                self.is_work = first_is_work(&self.sequence);
+                self.is_postponed = false;
+            }
+        }
+        self.repeat = conf.sequence.repeat;
+    }
+
+    pub fn is_work(&self) -> bool {
+        self.is_work
+    }
+
+    pub fn is_pause(&self) -> bool {
+        !self.stopwatch.is_running()
+    }
+
+    /// Is the current phase (or the break after the current work phase) a
+    /// long one?
+    pub fn is_long_break(&self) -> bool {
+        self.sequence.is_empty() && self.long_break_every > 0 && self.cycle >= self.long_break_every
+    }
+
+    /// Number of the current work cycle and the number of cycles between
+    /// long breaks. `None` if long breaks are disabled. For a phase sequence
+    /// - number of the current phase and the number of phases
+    pub fn cycle(&self) -> Option<(u8, u8)> {
+        if !self.sequence.is_empty() {
+            let len = self.sequence.len().min(u8::MAX as usize) as u8;
+            return Some(((self.step + 1).min(len as usize) as u8, len));
+        }
+        match self.long_break_every {
+            0 => None,
+            every => Some((self.cycle, every)),
+        }
+    }
+
+    /// Index of the current phase in the sequence. `None` without a sequence
+    /// and while the user works after postponing a break
+    pub fn phase_index(&self) -> Option<usize> {
+        match self.is_postponed || self.sequence.is_empty() {
+            true => None,
+            false => Some(self.step),
+        }
+    }
+
+    /// The current phase of the sequence (see [`Timer::phase_index`])
+    pub fn phase(&self) -> Option<&Phase> {
+        self.phase_index().and_then(|idx| self.sequence_phase(idx))
+    }
+
+    pub fn sequence_phase(&self, idx: usize) -> Option<&Phase> {
+        self.sequence.get(idx)
+    }
+
+    /// How many more times the current break can be postponed
+    pub fn snoozes_left(&self) -> u8 {
+        self.max_snoozes.saturating_sub(self.snoozes)
+    }
+
+    /// Can the current phase be postponed? Only breaks can
+    pub fn can_postpone(&self) -> bool {
+        !self.is_work && self.snoozes_left() > 0
+    }
+
+    pub fn snooze_time(&self) -> Time {
+        self.snooze_time
+    }
+
+    /// Duration of the current phase
+    pub fn phase_time(&self) -> Time {
+        let time = match self.phase() {
+            _ if self.is_work && self.is_postponed => self.snooze_time,
+            Some(phase) => phase.time,
+            None => match (self.is_work, self.is_long_break()) {
+                (true, _) => self.work_time,
+                (false, false) => self.free_time,
+                (false, true) => self.long_break_time,
+            },
+        };
+
+        time + self.extra_time
+    }
+
+    /// Switches to the next phase of the cycle. After a break the next work
+    /// cycle begins; after a long break the cycles are counted anew. Returns
+    /// `false` if a non-repeating sequence is over
+    fn next_phase(&mut self) -> bool {
+        if !self.is_work {
+            self.cycle = match self.is_long_break() {
+                true => 1,
+                false => self.cycle.saturating_add(1),
+            };
+            self.snoozes = 0;
+        }
+
+        if self.sequence.is_empty() {
+            self.is_work = !self.is_work;
+        } else if !self.is_postponed {
+            // После отложенного перерыва начинается тот же самый перерыв, а
+            // не следующая фаза
+            self.step += 1;
+            if self.step >= self.sequence.len() {
+                if !self.repeat {
+                    return false;
+                }
+                self.step = 0;
+            }
+            self.is_work = self.sequence[self.step].is_work;
+        } else {
+            self.is_work = self.sequence[self.step].is_work;
+        }
+
+        self.is_postponed = false;
+        self.extra_time = Time::ZERO;
+        self.warned = [false; 2];
+        if self.is_work {
+            self.apply_pending();
+        }
+        true
+    }
+
+    /// Number of phases after which the cycle repeats
+    fn cycle_len(&self) -> usize {
+        match self.sequence.is_empty() {
+            true => 2 * self.long_break_every.max(1) as usize,
+            false => self.sequence.len(),
+        }
+    }
+
+    /// Is the phase after the current one a break?
+    fn next_is_break(&self) -> bool {
+        if self.sequence.is_empty() || self.is_postponed {
+            return true;
+        }
+        match self.sequence.get(self.step + 1) {
+            Some(phase) => !phase.is_work,
+            None if self.repeat => !self.sequence[0].is_work,
+            None => false,
+        }
+    }
+
+    /// Index of the nearest work phase of the sequence after a break: the
+    /// current one if the user is working, otherwise the next one
+    fn next_work_step(&self) -> usize {
+        let len = self.sequence.len();
+        if self.is_work && !self.is_postponed {
+            return self.step;
+        }
+        (1..=len)
+            .map(|offset| (self.step + offset) % len)
+            .find(|&idx| self.sequence[idx].is_work)
+            .unwrap_or(self.step)
+    }
+
+    /// The last phase of a non-repeating sequence is over: the timer stops
+    /// at the beginning of the sequence
+    fn finish_sequence(&mut self, now: Instant) -> Vec<TimerEvent> {
+        let mut events = self.reset(now);
+        events.retain(|event| *event != TimerEvent::Stopped);
+        events.push(TimerEvent::SequenceFinished);
+        events
+    }
+
+    /// The event about the end of the current phase
+    fn finished(&self, outcome: PhaseOutcome, elapsed: Time) -> TimerEvent {
+        TimerEvent::PhaseFinished {
+            is_work: self.is_work,
+            is_long_break: !self.is_work && self.is_long_break(),
+            phase: self.phase_index(),
+            elapsed,
+            planned: self.phase_time(),
+            outcome,
+        }
+    }
+
+    fn phase_duration(&self) -> Duration {
+        self.phase_time().to_duration()
+    }
+
+    /// The moment the current phase ends. `None` while the timer is paused
+    pub fn deadline(&self) -> Option<Instant> {
+        self.stopwatch.deadline(self.phase_duration())
+    }
+
+    /// Time counted in the current phase
+    pub fn elapsed(&self, now: Instant) -> Time {
+        Time::from_duration(self.stopwatch.elapsed(now))
+    }
+
+    /// Time left until the end of the current phase (rounded up)
+    pub fn remaining_time(&self, now: Instant) -> Time {
+        self.stopwatch.remaining(self.phase_duration(), now)
+    }
+
+    pub fn handle(&mut self, cmd: TimerCommand, now: Instant) -> Vec<TimerEvent> {
+        match cmd {
+            TimerCommand::Start => self.start(now),
+            TimerCommand::Pause => self.pause(now),
+            TimerCommand::PauseFor(len) => self.pause_for(len, now),
+            TimerCommand::Stop => self.stop(now),
+            TimerCommand::Tick => self.tick(now),
+            TimerCommand::Away(away) => self.away(away, now),
+            TimerCommand::Postpone => self.postpone(now),
+            TimerCommand::Skip => self.skip(now),
+            TimerCommand::Extend(by) => self.extend(by),
+        }
+    }
+
+    /// The current state for saving. `saved_at` is the current date&time in
+    /// UNIX Timestamp format
+    pub fn state(&self, saved_at: u64, now: Instant) -> TimerState {
+        TimerState {
+            saved_at,
+            is_work: self.is_work,
+            is_pause: self.is_pause(),
+            elapsed: self.elapsed(now),
+            cycle: self.cycle,
+            snoozes: self.snoozes,
+            is_postponed: self.is_postponed,
+            extra_time: self.extra_time,
+            step: self.step,
+            pause_left: self.pause_left(now),
+        }
+    }
+
+    /// Restores the saved state. `gap` is the wall-clock time passed since
+    /// the state was saved. If the timer was running, the gap is counted: if
+    /// it is not shorter than a break, it is a break; otherwise it is added
+    /// to the phase and the phases that have ended meanwhile are switched
+    pub fn restore(&mut self, state: &TimerState, gap: Duration, now: Instant) -> Vec<TimerEvent> {
+        self.is_work = state.is_work;
+        self.cycle = state.cycle.clamp(1, self.long_break_every.max(1));
+        self.snoozes = state.snoozes;
+        self.is_postponed = state.is_postponed;
+        self.extra_time = state.extra_time;
+        // Последовательность фаз могла измениться, пока программа не работала
+        self.step = match state.step < self.sequence.len() {
+            true => state.step,
+            false => 0,
+        };
+
+        let elapsed = state.elapsed.to_duration();
+        if state.is_pause {
+            self.stopwatch = Stopwatch::restored(elapsed, None);
+            // Если пауза истекла, пока программа не работала, таймер
+            // продолжит отсчёт со следующим тиком
+            self.pause_until = state
+                .pause_left
+                .map(|left| now + left.to_duration().saturating_sub(gap));
+            return Vec::new();
+        }
+
+        self.stopwatch = Stopwatch::restored(elapsed, Some(now));
+        let events = self.away(gap, now);
+        if !events.is_empty() {
+            return events;
+        }
+
+        self.stopwatch = Stopwatch::restored(elapsed + gap, Some(now));
+        self.tick(now)
+    }
+
+    /// Time left until the end of the timed pause (rounded up). `None` if
+    /// there is no timed pause
+    pub fn pause_left(&self, now: Instant) -> Option<Time> {
+        self.pause_until
+            .map(|until| Time::from_duration_ceil(until.saturating_duration_since(now)))
+    }
+
+    fn start(&mut self, now: Instant) -> Vec<TimerEvent> {
+        if !self.is_pause() {
+            return Vec::new();
+        }
+        self.pause_until = None;
+        self.stopwatch.resume(now);
+        vec![TimerEvent::Resumed]
+    }
+
+    fn pause(&mut self, now: Instant) -> Vec<TimerEvent> {
+        if self.is_pause() {
+            return Vec::new();
+        }
+        self.pause_until = None;
+        self.stopwatch.pause(now);
+        vec![TimerEvent::Paused]
+    }
+
+    fn pause_for(&mut self, len: Time, now: Instant) -> Vec<TimerEvent> {
+        let events = self.pause(now);
+        if !events.is_empty() {
+            self.pause_until = Some(now + len.to_duration());
+        }
+        events
+    }
+
+    fn stop(&mut self, now: Instant) -> Vec<TimerEvent> {
+        // Оборванная фаза тоже попадает в статистику
+        let elapsed = self.elapsed(now);
+        let mut events = Vec::new();
+        if !elapsed.is_zero() {
+            events.push(self.finished(PhaseOutcome::Stopped, elapsed));
+        }
+        events.extend(self.reset(now));
+
+        events
+    }
+
+    /// Resets the timer to the beginning of the work phase and pauses it
+    fn reset(&mut self, now: Instant) -> Vec<TimerEvent> {
+        /* После того, как пользователь нажмёт на "Стоп", нам нужно сбросить
+         * таймер, после чего установить рабочее время (а не время отдыха) и
+         * поставить счётчик (таймер) на паузу.
+         */
+        let mut events = self.pause(now);
+
+        self.pause_until = None;
+        self.apply_pending();
+        self.stopwatch.reset();
+        self.step = 0;
+        self.is_work = first_is_work(&self.sequence);
+        self.is_postponed = false;
+        self.extra_time = Time::ZERO;
+        self.warned = [false; 2];
+        self.cycle = 1;
+        self.snoozes = 0;
+        events.push(TimerEvent::Stopped);
+
+        events
+    }
+
+    fn tick(&mut self, now: Instant) -> Vec<TimerEvent> {
+        let mut events = Vec::new();
+
+        // Пауза с заданной длиной заканчивается сама
+        if let Some(until) = self.pause_until
+            && now >= until
+        {
+            self.pause_until = None;
+            self.stopwatch.resume(until);
+            events.push(TimerEvent::PauseExpired);
+        }
+
+        /* Тик лишь сверяет часы: фаза заканчивается по своему дедлайну, даже
+         * если между тиками прошло больше секунды (цикл событий подвис,
+         * процесс был заторможен и т.д.). Следующая фаза отсчитывается от
+         * дедлайна предыдущей, поэтому опоздание тиков не копится.
+         */
+        // Фаза нулевой длины заканчивается сразу. Если таковы все фазы
+        // цикла, отсчёт никогда не догонит часы - таймер останавливается
+        let mut empty = 0;
+        while let Some(deadline) = self.deadline() {
+            if now < deadline {
+                break;
+            }
+
+            events.push(self.finished(PhaseOutcome::Finished, self.phase_time()));
+            if !self.next_phase() {
+                events.extend(self.finish_sequence(now));
+                return events;
+            }
+            self.stopwatch.restart(deadline);
+            events.push(TimerEvent::PhaseStarted {
+                is_work: self.is_work,
+                is_long_break: !self.is_work && self.is_long_break(),
+                phase: self.phase_index(),
+            });
+
+            empty = match self.phase_time().is_zero() {
+                true => empty + 1,
+                false => 0,
+            };
+            if empty > self.cycle_len() {
+                events.extend(self.reset(now));
+                return events;
+            }
+        }
+        events.extend(self.warn(now));
+
+        events
+    }
+
+    /// Warns about the coming break. Each warning is sent once per phase, so
+    /// a pause does not repeat it. A warning whose lead time is not shorter
+    /// than the whole phase is not sent at all
+    fn warn(&mut self, now: Instant) -> Option<TimerEvent> {
+        if !self.is_work || self.is_pause() || !self.next_is_break() {
+            return None;
+        }
+
+        let left = self.remaining_time(now);
+        let phase_time = self.phase_time();
+        let mut event = None;
+
+        // Если подошёл срок сразу обоих предупреждений (например, после сна
+        // системы), то показываем только последнее из них
+        for (idx, lead) in self.warnings.into_iter().enumerate() {
+            if lead.is_zero() || lead >= phase_time || self.warned[idx] || left > lead {
+                continue;
+            }
+            self.warned[idx] = true;
+            event = Some(TimerEvent::BreakWarning {
+                is_final: idx == 1,
+                left,
+            });
+        }
+
+        event
+    }
+
+    fn away(&mut self, away: Duration, now: Instant) -> Vec<TimerEvent> {
+        let elapsed = self.stopwatch.elapsed(now);
+        if away < self.free_time.to_duration() {
+            // Короткое отсутствие во время перерыва - тоже отдых
+            if !self.is_work {
+                self.stopwatch.set_elapsed(elapsed + away, now);
+            }
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        let mut rest = away;
+        match self.is_work {
+            // Работа до ухода тоже попадает в статистику и в дневную норму
+            true if !elapsed.is_zero() => {
+                events.push(self.finished(PhaseOutcome::Stopped, Time::from_duration(elapsed)))
+            }
+            true => {}
+            // Начатый перерыв продолжился отсутствием
+            false => rest += elapsed,
+        }
+
+        // Отсутствие не короче длинного перерыва засчитывается за длинный
+        // перерыв, и циклы начинают считаться заново
+        let is_long = rest >= self.long_break_time.to_duration();
+        self.cycle = match (is_long, self.is_long_break()) {
+            (true, _) => 1,
+            // Длинный перерыв всё ещё положен
+            (false, true) => self.cycle,
+            (false, false) => self.cycle.saturating_add(1),
+        };
+        self.apply_pending();
+        // В последовательности отдых засчитывается за ближайший перерыв
+        if !self.sequence.is_empty() {
+            self.step = self.next_work_step();
+        }
+        self.is_work = self
+            .sequence
+            .get(self.step)
+            .is_none_or(|phase| phase.is_work);
+        self.is_postponed = false;
+        self.extra_time = Time::ZERO;
+        self.warned = [false; 2];
+        self.snoozes = 0;
+        self.stopwatch.restart(now);
+
+        events.push(TimerEvent::NaturalBreak {
+            elapsed: Time::from_duration(rest),
+        });
+        events.push(TimerEvent::PhaseStarted {
+            is_work: self.is_work,
+            is_long_break: false,
+            phase: self.phase_index(),
+        });
+        events
+    }
+
+    fn postpone(&mut self, now: Instant) -> Vec<TimerEvent> {
+        if !self.can_postpone() {
+            return Vec::new();
+        }
+
+        // Перерыв не засчитывается: цикл остаётся тем же, и после
+        // дополнительной работы начнётся тот же (возможно, длинный) перерыв
+        let finished = self.finished(PhaseOutcome::Postponed, self.elapsed(now));
+        self.snoozes += 1;
+        self.is_work = true;
+        self.is_postponed = true;
+        self.extra_time = Time::ZERO;
+        self.warned = [false; 2];
+        self.stopwatch.restart(now);
+
+        vec![
+            finished,
+            TimerEvent::Postponed {
+                by: self.snooze_time,
+                snoozes_left: self.snoozes_left(),
+            },
+        ]
+    }
+
+    fn skip(&mut self, now: Instant) -> Vec<TimerEvent> {
+        // Фаза закрывается с реально прошедшим временем, чтобы в статистике
+        // было видно, что она оборвалась раньше
+        let finished = self.finished(PhaseOutcome::Skipped, self.elapsed(now));
+
+        if !self.next_phase() {
+            let mut events = vec![finished];
+            events.extend(self.finish_sequence(now));
+            return events;
+        }
+        // Пользователь сам начал следующую фазу, поэтому пауза снимается
+        self.stopwatch = Stopwatch::started(now);
+        self.pause_until = None;
+
+        vec![
+            finished,
+            TimerEvent::PhaseStarted {
+                is_work: self.is_work,
+                is_long_break: !self.is_work && self.is_long_break(),
+                phase: self.phase_index(),
+            },
+        ]
+    }
+
+    fn extend(&mut self, by: Time) -> Vec<TimerEvent> {
+        // Отправленные предупреждения не сбрасываются: о перерыве уже
+        // предупредили, а ещё не отправленные придут до нового дедлайна
+        self.extra_time += by;
+        vec![TimerEvent::Extended { by }]
+    }
+}
+
+/// Pause-aware measurement of the elapsed time
+#[derive(Debug, Clone, Copy)]
+pub struct Stopwatch {
+    /// Time counted before the last pause
+    elapsed_before: Duration,
+
+    /// The moment the stopwatch was last started or resumed. `None` while it
+    /// is paused
+    resumed_at: Option<Instant>,
+}
+
+impl Stopwatch {
+    pub fn started(now: Instant) -> Self {
+        Self {
+            elapsed_before: Duration::ZERO,
+            resumed_at: Some(now),
+        }
+    }
+
+    /// Stopwatch which has already counted `elapsed`. It runs from
+    /// `resumed_at` or is paused if `resumed_at` is `None`
+    pub fn restored(elapsed: Duration, resumed_at: Option<Instant>) -> Self {
+        Self {
+            elapsed_before: elapsed,
+            resumed_at,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.resumed_at.is_some()
+    }
+
+    /// Time counted at the moment `now`
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        let running = self
+            .resumed_at
+            .map(|resumed_at| now.saturating_duration_since(resumed_at))
+            .unwrap_or_default();
+
+        self.elapsed_before + running
+    }
+
+    /// The moment the stopwatch counts `len`. `None` while it is paused
+    pub fn deadline(&self, len: Duration) -> Option<Instant> {
+        let left = len.saturating_sub(self.elapsed_before);
+        self.resumed_at.map(|resumed_at| resumed_at + left)
+    }
+
+    /// Time left until the stopwatch counts `len` (rounded up)
+    pub fn remaining(&self, len: Duration, now: Instant) -> Time {
+        Time::from_duration_ceil(len.saturating_sub(self.elapsed(now)))
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        self.elapsed_before = self.elapsed(now);
+        self.resumed_at = None;
+    }
+
+    pub fn resume(&mut self, now: Instant) {
+        if self.resumed_at.is_none() {
+            self.resumed_at = Some(now);
+        }
+    }
+
+    /// Starts counting from zero at the moment `at`. A paused stopwatch only
+    /// drops the counted time and stays paused
+    pub fn restart(&mut self, at: Instant) {
+        self.elapsed_before = Duration::ZERO;
+        if self.resumed_at.is_some() {
+            self.resumed_at = Some(at);
+        }
+    }
+
+    /// Replaces the counted time with `elapsed` at the moment `now`
+    pub fn set_elapsed(&mut self, elapsed: Duration, now: Instant) {
+        self.elapsed_before = elapsed;
+        if self.resumed_at.is_some() {
+            self.resumed_at = Some(now);
+        }
+    }
+
+    /// Drops the counted time and pauses the stopwatch
+    pub fn reset(&mut self) {
+        self.elapsed_before = Duration::ZERO;
+        self.resumed_at = None;
+    }
+}
+
+/// Does the sequence begin with work? Without a sequence the timer always
+/// begins with work
+fn first_is_work(phases: &[Phase]) -> bool {
+    phases.first().is_none_or(|phase| phase.is_work)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 60 minutes of work, 15 minutes of break, no warnings
+    fn conf() -> Config {
+        Config {
+            break_warning: Time::ZERO,
+            break_final_warning: Time::ZERO,
+            ..Default::default()
+        }
+    }
+
+    fn mins(start: Instant, mins: u64) -> Instant {
+        start + Duration::from_secs(mins * 60)
+    }
+
+    fn secs(start: Instant, secs: u64) -> Instant {
+        start + Duration::from_secs(secs)
+    }
+
+    fn started(is_work: bool) -> TimerEvent {
+        TimerEvent::PhaseStarted {
+            is_work,
+            is_long_break: false,
+            phase: None,
+        }
+    }
+
+    fn finished(is_work: bool, elapsed: Time, planned: Time, outcome: PhaseOutcome) -> TimerEvent {
+        TimerEvent::PhaseFinished {
+            is_work,
+            is_long_break: false,
+            phase: None,
+            elapsed,
+            planned,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn work_break_work() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+        let work = Time::from_mins(60);
+        let free = Time::from_mins(15);
+
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 59)).is_empty());
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 60)),
+            vec![
+                finished(true, work, work, PhaseOutcome::Finished),
+                started(false)
+            ]
+        );
+        assert!(!timer.is_work());
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 75)),
+            vec![
+                finished(false, free, free, PhaseOutcome::Finished),
+                started(true)
+            ]
+        );
+        assert!(timer.is_work());
+        assert_eq!(timer.elapsed(mins(t0, 75)), Time::ZERO);
+    }
+
+    #[test]
+    fn pause_keeps_elapsed() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        assert_eq!(
+            timer.handle(TimerCommand::Pause, mins(t0, 10)),
+            vec![TimerEvent::Paused]
+        );
+        assert!(timer.is_pause());
+        // Пока таймер на паузе, время не идёт и фаза не заканчивается
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 100)).is_empty());
+        assert_eq!(timer.elapsed(mins(t0, 100)), Time::from_mins(10));
+
+        assert_eq!(
+            timer.handle(TimerCommand::Start, mins(t0, 100)),
+            vec![TimerEvent::Resumed]
+        );
+        assert_eq!(timer.elapsed(mins(t0, 120)), Time::from_mins(30));
+        assert_eq!(timer.remaining_time(mins(t0, 120)), Time::from_mins(30));
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 150))[1],
+            started(false)
+        );
+    }
+
+    #[test]
+    fn stop_records_phase() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+        let work = Time::from_mins(60);
+
+        let events = timer.handle(TimerCommand::Stop, mins(t0, 10));
+        assert_eq!(
+            events.first(),
+            Some(&finished(
+                true,
+                Time::from_mins(10),
+                work,
+                PhaseOutcome::Stopped
+            ))
+        );
+        assert_eq!(events.last(), Some(&TimerEvent::Stopped));
+        assert!(timer.is_pause());
+        assert!(timer.is_work());
+        assert_eq!(timer.elapsed(mins(t0, 20)), Time::ZERO);
+
+        // Повторная остановка ничего не записывает
+        assert_eq!(
+            timer.handle(TimerCommand::Stop, mins(t0, 20)),
+            vec![TimerEvent::Stopped]
+        );
+    }
+
+    #[test]
+    fn late_tick_catches_up() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+        let work = Time::from_mins(60);
+        let free = Time::from_mins(15);
+
+        // Первый тик через 2 ч 20 мин: прошли работа, перерыв, ещё одна
+        // работа, и идёт пятая минута второго перерыва
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 140)),
+            vec![
+                finished(true, work, work, PhaseOutcome::Finished),
+                started(false),
+                finished(false, free, free, PhaseOutcome::Finished),
+                started(true),
+                finished(true, work, work, PhaseOutcome::Finished),
+                started(false),
+            ]
+        );
+        assert_eq!(timer.elapsed(mins(t0, 140)), Time::from_mins(5));
+        assert_eq!(timer.remaining_time(mins(t0, 140)), Time::from_mins(10));
+    }
+
+    #[test]
+    fn away_records_interrupted_work() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+        let work = Time::from_mins(60);
+
+        // Экран заблокирован на 20-й минуте работы на час
+        timer.handle(TimerCommand::Pause, mins(t0, 20));
+        assert_eq!(
+            timer.handle(TimerCommand::Away(Duration::from_secs(3600)), mins(t0, 20)),
+            vec![
+                finished(true, Time::from_mins(20), work, PhaseOutcome::Stopped),
+                TimerEvent::NaturalBreak {
+                    elapsed: Time::from_mins(60)
+                },
+                started(true),
+            ]
+        );
+        assert!(timer.is_pause());
+        assert_eq!(timer.elapsed(mins(t0, 20)), Time::ZERO);
+    }
+
+    #[test]
+    fn away_continues_break() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+        timer.handle(TimerCommand::Tick, mins(t0, 60));
+        assert!(!timer.is_work());
+
+        // Короткое отсутствие засчитывается в перерыв
+        timer.handle(TimerCommand::Pause, mins(t0, 65));
+        assert!(
+            timer
+                .handle(TimerCommand::Away(Duration::from_secs(300)), mins(t0, 65))
+                .is_empty()
+        );
+        assert_eq!(timer.elapsed(mins(t0, 65)), Time::from_mins(10));
+
+        // Длинное - тоже, вместе с уже прошедшей частью перерыва
+        assert_eq!(
+            timer.handle(TimerCommand::Away(Duration::from_secs(1200)), mins(t0, 65)),
+            vec![
+                TimerEvent::NaturalBreak {
+                    elapsed: Time::from_mins(30)
+                },
+                started(true),
+            ]
+        );
+    }
+
+    #[test]
+    fn restore_records_interrupted_work() {
+        let t0 = Instant::now();
+        let timer = Timer::new(&conf(), t0);
+        let state = timer.state(0, mins(t0, 30));
+
+        let mut restored = Timer::new(&conf(), t0);
+        let events = restored.restore(&state, Duration::from_secs(8 * 3600), t0);
+        assert_eq!(
+            events.first(),
+            Some(&finished(
+                true,
+                Time::from_mins(30),
+                Time::from_mins(60),
+                PhaseOutcome::Stopped
+            ))
+        );
+        assert!(restored.is_work());
+        assert!(!restored.is_pause());
+    }
+
+    #[test]
+    fn zero_phases_stop_timer() {
+        let t0 = Instant::now();
+        let mut conf = conf();
+        conf.work_time = Time::ZERO;
+        conf.free_time = Time::ZERO;
+        let mut timer = Timer::new(&conf, t0);
+
+        let events = timer.handle(TimerCommand::Tick, mins(t0, 1));
+        assert_eq!(events.last(), Some(&TimerEvent::Stopped));
+        assert!(timer.is_pause());
+    }
+
+    /// The config with new intervals for `policy`
+    fn changed(policy: IntervalPolicy, work: u32, free: u32) -> Config {
+        Config {
+            work_time: Time::from_mins(work),
+            free_time: Time::from_mins(free),
+            interval_policy: policy,
+            ..conf()
+        }
+    }
+
+    #[test]
+    fn apply_now_ends_longer_phase() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.set_intervals(&changed(IntervalPolicy::ApplyNow, 30, 15), mins(t0, 40));
+        assert_eq!(timer.phase_time(), Time::from_mins(30));
+        assert_eq!(timer.remaining_time(mins(t0, 40)), Time::ZERO);
+        // Фаза заканчивается сейчас и длится новые 30 минут, а не 40
+        let events = timer.handle(TimerCommand::Tick, mins(t0, 40));
+        assert_eq!(
+            events[0],
+            finished(
+                true,
+                Time::from_mins(30),
+                Time::from_mins(30),
+                PhaseOutcome::Finished
+            )
+        );
+        assert!(!timer.is_work());
+    }
+
+    #[test]
+    fn scale_keeps_part_of_phase() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.set_intervals(&changed(IntervalPolicy::Scale, 120, 15), mins(t0, 30));
+        assert_eq!(timer.elapsed(mins(t0, 30)), Time::from_mins(60));
+        assert_eq!(timer.remaining_time(mins(t0, 30)), Time::from_mins(60));
+    }
+
+    #[test]
+    fn scale_from_zero_length() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&changed(IntervalPolicy::Scale, 0, 15), t0);
+
+        // Масштабировать нечего - прошедшее время не больше новой длины
+        timer.set_intervals(&changed(IntervalPolicy::Scale, 60, 15), mins(t0, 10));
+        assert_eq!(timer.elapsed(mins(t0, 10)), Time::from_mins(10));
+        assert_eq!(timer.phase_time(), Time::from_mins(60));
+    }
+
+    #[test]
+    fn next_cycle_waits_for_work_phase() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.set_intervals(&changed(IntervalPolicy::NextCycle, 30, 10), mins(t0, 10));
+        assert_eq!(timer.phase_time(), Time::from_mins(60));
+        assert_eq!(timer.elapsed(mins(t0, 10)), Time::from_mins(10));
+
+        // Перерыв после этой работы тоже ещё старый
+        timer.handle(TimerCommand::Tick, mins(t0, 60));
+        assert!(!timer.is_work());
+        assert_eq!(timer.phase_time(), Time::from_mins(15));
+
+        timer.handle(TimerCommand::Tick, mins(t0, 75));
+        assert!(timer.is_work());
+        assert_eq!(timer.phase_time(), Time::from_mins(30));
+    }
+
+    #[test]
+    fn next_cycle_applies_on_stop() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.set_intervals(&changed(IntervalPolicy::NextCycle, 30, 10), mins(t0, 10));
+        timer.handle(TimerCommand::Stop, mins(t0, 20));
+        assert_eq!(timer.phase_time(), Time::from_mins(30));
+    }
+
+    #[test]
+    fn next_cycle_applies_on_away() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.set_intervals(&changed(IntervalPolicy::NextCycle, 30, 10), mins(t0, 10));
+        timer.handle(TimerCommand::Pause, mins(t0, 20));
+        timer.handle(TimerCommand::Away(Duration::from_secs(3600)), mins(t0, 20));
+        assert!(timer.is_work());
+        assert_eq!(timer.phase_time(), Time::from_mins(30));
+    }
+
+    #[test]
+    fn next_cycle_on_stopped_timer() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+        timer.handle(TimerCommand::Stop, t0);
+
+        // Фаза ещё не началась - откладывать нечего
+        timer.set_intervals(&changed(IntervalPolicy::NextCycle, 30, 10), mins(t0, 5));
+        assert_eq!(timer.phase_time(), Time::from_mins(30));
+    }
+
+    fn warning(is_final: bool, left: Time) -> TimerEvent {
+        TimerEvent::BreakWarning { is_final, left }
+    }
+
+    #[test]
+    fn warnings_once_per_phase() {
+        let t0 = Instant::now();
+        // Предупреждения за 5 минут и за 30 секунд до перерыва
+        let mut timer = Timer::new(&Config::default(), t0);
+        let early = warning(false, Time::from_mins(5));
+        let last = warning(true, Time::from_secs(30));
+
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 54)).is_empty());
+        assert_eq!(timer.handle(TimerCommand::Tick, mins(t0, 55)), vec![early]);
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 56)).is_empty());
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, secs(t0, 59 * 60 + 30)),
+            vec![last]
+        );
+        assert!(
+            timer
+                .handle(TimerCommand::Tick, secs(t0, 59 * 60 + 45))
+                .is_empty()
+        );
+
+        // Во время перерыва предупреждать не о чем
+        assert!(
+            !timer
+                .handle(TimerCommand::Tick, mins(t0, 60))
+                .contains(&last)
+        );
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 70)).is_empty());
+
+        // В следующей рабочей фазе - снова по одному разу
+        timer.handle(TimerCommand::Tick, mins(t0, 75));
+        assert!(timer.is_work());
+        assert_eq!(timer.handle(TimerCommand::Tick, mins(t0, 130)), vec![early]);
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 131)).is_empty());
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, secs(t0, 134 * 60 + 30)),
+            vec![last]
+        );
+    }
+
+    #[test]
+    fn warning_not_repeated_after_pause() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&Config::default(), t0);
+
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 55)),
+            vec![warning(false, Time::from_mins(5))]
+        );
+        timer.handle(TimerCommand::Pause, mins(t0, 56));
+        timer.handle(TimerCommand::Start, mins(t0, 80));
+        // До перерыва снова меньше 5 минут, но об этом уже предупредили
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 80)).is_empty());
+
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, secs(t0, 83 * 60 + 30)),
+            vec![warning(true, Time::from_secs(30))]
+        );
+        timer.handle(TimerCommand::Pause, secs(t0, 83 * 60 + 40));
+        timer.handle(TimerCommand::Start, mins(t0, 90));
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 90)).is_empty());
+    }
+
+    #[test]
+    fn warnings_suppressed_for_short_phase() {
+        let t0 = Instant::now();
+        let last = warning(true, Time::from_secs(30));
+
+        // Работа короче предупреждения за 5 минут: оно не отправляется,
+        // хотя до перерыва с самого начала меньше 5 минут
+        for work in [4, 5] {
+            let conf = Config {
+                work_time: Time::from_secs(work as u32 * 60),
+                ..Default::default()
+            };
+            let mut timer = Timer::new(&conf, t0);
+            assert!(timer.handle(TimerCommand::Tick, t0).is_empty());
+            assert!(timer.handle(TimerCommand::Tick, mins(t0, 1)).is_empty());
+            assert_eq!(
+                timer.handle(TimerCommand::Tick, secs(t0, work * 60 - 30)),
+                vec![last]
+            );
+        }
+
+        // Предупреждение за 30 секунд тоже не отправляется, если фаза не
+        // длиннее его
+        let conf = Config {
+            work_time: Time::from_secs(30),
+            ..Default::default()
+        };
+        let mut timer = Timer::new(&conf, t0);
+        assert!(timer.handle(TimerCommand::Tick, t0).is_empty());
+        assert!(timer.handle(TimerCommand::Tick, secs(t0, 10)).is_empty());
+    }
+
+    /// The config where a break can be postponed `max_snoozes` times
+    fn snoozes(max_snoozes: u8) -> Config {
+        Config {
+            max_snoozes,
+            ..conf()
+        }
+    }
+
+    fn postponed(snoozes_left: u8) -> TimerEvent {
+        TimerEvent::Postponed {
+            by: Time::from_mins(5),
+            snoozes_left,
+        }
+    }
+
+    #[test]
+    fn postpone_limit() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&snoozes(2), t0);
+        let snooze = Time::from_mins(5);
+
+        // Работу откладывать нельзя
+        assert!(
+            timer
+                .handle(TimerCommand::Postpone, mins(t0, 10))
+                .is_empty()
+        );
+        assert!(timer.is_work());
+
+        timer.handle(TimerCommand::Tick, mins(t0, 60));
+        assert_eq!(
+            timer.handle(TimerCommand::Postpone, mins(t0, 61)),
+            vec![
+                finished(
+                    false,
+                    Time::from_mins(1),
+                    Time::from_mins(15),
+                    PhaseOutcome::Postponed
+                ),
+                postponed(1),
+            ]
+        );
+        assert!(timer.is_work());
+        assert_eq!(timer.phase_time(), snooze);
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 66)),
+            vec![
+                finished(true, snooze, snooze, PhaseOutcome::Finished),
+                started(false)
+            ]
+        );
+
+        assert_eq!(
+            timer.handle(TimerCommand::Postpone, mins(t0, 66))[1],
+            postponed(0)
+        );
+        timer.handle(TimerCommand::Tick, mins(t0, 71));
+        assert!(!timer.can_postpone());
+        assert!(
+            timer
+                .handle(TimerCommand::Postpone, mins(t0, 71))
+                .is_empty()
+        );
+        assert!(!timer.is_work());
+    }
+
+    #[test]
+    fn postpone_disabled() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&snoozes(0), t0);
+
+        timer.handle(TimerCommand::Tick, mins(t0, 60));
+        assert!(!timer.can_postpone());
+        assert!(
+            timer
+                .handle(TimerCommand::Postpone, mins(t0, 61))
+                .is_empty()
+        );
+        assert!(!timer.is_work());
+        assert_eq!(timer.elapsed(mins(t0, 61)), Time::from_mins(1));
+    }
+
+    #[test]
+    fn postponed_long_break_resumes() {
+        let t0 = Instant::now();
+        let conf = Config {
+            long_break_every: 2,
+            ..snoozes(2)
+        };
+        let mut timer = Timer::new(&conf, t0);
+        let snooze = Time::from_mins(5);
+        let long = Time::from_mins(30);
+        let long_started = TimerEvent::PhaseStarted {
+            is_work: false,
+            is_long_break: true,
+            phase: None,
+        };
+
+        // Работа, перерыв, работа и длинный перерыв
+        timer.handle(TimerCommand::Tick, mins(t0, 75));
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 135))[1],
+            long_started
+        );
+        assert_eq!(
+            timer.handle(TimerCommand::Postpone, mins(t0, 135))[0],
+            TimerEvent::PhaseFinished {
+                is_work: false,
+                is_long_break: true,
+                phase: None,
+                elapsed: Time::ZERO,
+                planned: long,
+                outcome: PhaseOutcome::Postponed,
+            }
+        );
+
+        // После доработки начинается тот же длинный перерыв
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 140)),
+            vec![
+                finished(true, snooze, snooze, PhaseOutcome::Finished),
+                long_started
+            ]
+        );
+        assert_eq!(timer.phase_time(), long);
+        assert_eq!(timer.cycle(), Some((2, 2)));
+    }
+
+    #[test]
+    fn snoozes_reset_next_cycle() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&snoozes(1), t0);
+
+        timer.handle(TimerCommand::Tick, mins(t0, 60));
+        timer.handle(TimerCommand::Postpone, mins(t0, 60));
+        timer.handle(TimerCommand::Tick, mins(t0, 65));
+        assert!(!timer.can_postpone());
+
+        // Перерыв прошёл, в следующем цикле его снова можно отложить
+        timer.handle(TimerCommand::Tick, mins(t0, 80));
+        assert!(timer.is_work());
+        timer.handle(TimerCommand::Tick, mins(t0, 140));
+        assert!(!timer.is_work());
+        assert_eq!(timer.snoozes_left(), 1);
+        assert_eq!(
+            timer.handle(TimerCommand::Postpone, mins(t0, 140))[1],
+            postponed(0)
+        );
+    }
+
+    #[test]
+    fn skip_records_real_time() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        assert_eq!(
+            timer.handle(TimerCommand::Skip, mins(t0, 20)),
+            vec![
+                finished(
+                    true,
+                    Time::from_mins(20),
+                    Time::from_mins(60),
+                    PhaseOutcome::Skipped
+                ),
+                started(false),
+            ]
+        );
+        assert_eq!(timer.elapsed(mins(t0, 20)), Time::ZERO);
+        assert_eq!(
+            timer.handle(TimerCommand::Skip, mins(t0, 25)),
+            vec![
+                finished(
+                    false,
+                    Time::from_mins(5),
+                    Time::from_mins(15),
+                    PhaseOutcome::Skipped
+                ),
+                started(true),
+            ]
+        );
+        assert_eq!(timer.remaining_time(mins(t0, 25)), Time::from_mins(60));
+    }
+
+    #[test]
+    fn skip_resumes_paused_timer() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.handle(TimerCommand::Pause, mins(t0, 10));
+        // Время паузы в фазу не входит
+        assert_eq!(
+            timer.handle(TimerCommand::Skip, mins(t0, 30))[0],
+            finished(
+                true,
+                Time::from_mins(10),
+                Time::from_mins(60),
+                PhaseOutcome::Skipped
+            )
+        );
+        assert!(!timer.is_pause());
+        assert_eq!(timer.elapsed(mins(t0, 35)), Time::from_mins(5));
+    }
+
+    #[test]
+    fn extend_keeps_warnings() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&Config::default(), t0);
+        let by = Time::from_mins(5);
+
+        timer.handle(TimerCommand::Tick, mins(t0, 55));
+        assert_eq!(
+            timer.handle(TimerCommand::Extend(by), mins(t0, 56)),
+            vec![TimerEvent::Extended { by }]
+        );
+        assert_eq!(timer.phase_time(), Time::from_mins(65));
+        assert_eq!(timer.remaining_time(mins(t0, 56)), Time::from_mins(9));
+
+        // Предупреждение за 5 минут уже было, за 30 секунд - ещё нет
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 60)).is_empty());
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, secs(t0, 64 * 60 + 30)),
+            vec![warning(true, Time::from_secs(30))]
+        );
+
+        let long = Time::from_mins(65);
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 65)),
+            vec![
+                finished(true, long, long, PhaseOutcome::Finished),
+                started(false)
+            ]
+        );
+        // Добавка действует только на продлённую фазу
+        assert_eq!(timer.phase_time(), Time::from_mins(15));
+    }
+
+    /// Skips `breaks` work phases and reports whether each following break
+    /// is long
+    fn long_breaks(timer: &mut Timer, breaks: usize, now: Instant) -> Vec<bool> {
+        (0..breaks)
+            .map(|_| {
+                let events = timer.handle(TimerCommand::Skip, now);
+                let is_long = matches!(
+                    events[1],
+                    TimerEvent::PhaseStarted {
+                        is_work: false,
+                        is_long_break: true,
+                        ..
+                    }
+                );
+                assert_eq!(is_long, timer.is_long_break());
+                timer.handle(TimerCommand::Skip, now);
+                is_long
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_nth_break_is_long() {
+        let t0 = Instant::now();
+        let conf = Config {
+            long_break_every: 3,
+            ..conf()
+        };
+        let mut timer = Timer::new(&conf, t0);
+
+        assert_eq!(timer.cycle(), Some((1, 3)));
+        assert_eq!(
+            long_breaks(&mut timer, 7, t0),
+            [false, false, true, false, false, true, false]
+        );
+        assert_eq!(timer.cycle(), Some((2, 3)));
+
+        // Длинный перерыв длится long_break_time
+        timer.handle(TimerCommand::Skip, t0);
+        timer.handle(TimerCommand::Skip, t0);
+        timer.handle(TimerCommand::Skip, t0);
+        assert!(timer.is_long_break());
+        assert_eq!(timer.phase_time(), Time::from_mins(30));
+        timer.handle(TimerCommand::Tick, mins(t0, 30));
+        assert!(timer.is_work());
+        assert_eq!(timer.cycle(), Some((1, 3)));
+    }
+
+    #[test]
+    fn long_breaks_disabled() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        assert_eq!(timer.cycle(), None);
+        assert_eq!(long_breaks(&mut timer, 5, t0), [false; 5]);
+        assert_eq!(timer.cycle(), None);
+    }
+
+    #[test]
+    fn stop_resets_cycles() {
+        let t0 = Instant::now();
+        let conf = Config {
+            long_break_every: 3,
+            ..conf()
+        };
+        let mut timer = Timer::new(&conf, t0);
+
+        assert_eq!(long_breaks(&mut timer, 2, t0), [false, false]);
+        assert_eq!(timer.cycle(), Some((3, 3)));
+        assert!(timer.is_long_break());
+
+        timer.handle(TimerCommand::Stop, mins(t0, 10));
+        assert_eq!(timer.cycle(), Some((1, 3)));
+        timer.handle(TimerCommand::Start, mins(t0, 10));
+        assert_eq!(
+            long_breaks(&mut timer, 3, mins(t0, 10)),
+            [false, false, true]
+        );
+    }
+
+    /// Work 50 min, stretch 5 min, work 50 min, walk 15 min
+    fn sequence(repeat: bool) -> Config {
+        let phase = |name: &str, is_work, mins| Phase {
+            name: name.to_string(),
+            is_work,
+            time: Time::from_mins(mins),
+            color: None,
+            notification: None,
+        };
+        let mut conf = conf();
+        conf.sequence.phases = vec![
+            phase("Work", true, 50),
+            phase("Stretch", false, 5),
+            phase("Work", true, 50),
+            phase("Walk", false, 15),
+        ];
+        conf.sequence.repeat = repeat;
+        conf
+    }
+
+    /// Events of the switch from the phase `from` of the sequence to `to`
+    fn next_step(timer: &Timer, from: usize, to: usize) -> Vec<TimerEvent> {
+        let time = timer.sequence[from].time;
+        vec![
+            TimerEvent::PhaseFinished {
+                is_work: timer.sequence[from].is_work,
+                is_long_break: false,
+                phase: Some(from),
+                elapsed: time,
+                planned: time,
+                outcome: PhaseOutcome::Finished,
+            },
+            TimerEvent::PhaseStarted {
+                is_work: timer.sequence[to].is_work,
+                is_long_break: false,
+                phase: Some(to),
+            },
+        ]
+    }
+
+    #[test]
+    fn sequence_in_order() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&sequence(true), t0);
+
+        assert_eq!(timer.phase_index(), Some(0));
+        assert_eq!(timer.cycle(), Some((1, 4)));
+        assert_eq!(timer.phase_time(), Time::from_mins(50));
+
+        let ends = [50, 55, 105];
+        for (step, end) in ends.into_iter().enumerate() {
+            let events = timer.handle(TimerCommand::Tick, mins(t0, end));
+            assert_eq!(events, next_step(&timer, step, step + 1));
+        }
+        assert_eq!(timer.phase().map(|phase| phase.name.as_str()), Some("Walk"));
+        assert!(!timer.is_work());
+        assert!(!timer.is_long_break());
+        assert_eq!(timer.cycle(), Some((4, 4)));
+        assert_eq!(timer.phase_time(), Time::from_mins(15));
+    }
+
+    #[test]
+    fn sequence_repeats() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&sequence(true), t0);
+
+        timer.handle(TimerCommand::Tick, mins(t0, 105));
+        let events = timer.handle(TimerCommand::Tick, mins(t0, 120));
+        assert_eq!(events, next_step(&timer, 3, 0));
+        assert!(timer.is_work());
+        assert_eq!(timer.cycle(), Some((1, 4)));
+        assert_eq!(timer.elapsed(mins(t0, 120)), Time::ZERO);
+    }
+
+    #[test]
+    fn sequence_finishes() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&sequence(false), t0);
+
+        timer.handle(TimerCommand::Tick, mins(t0, 105));
+        let mut events = next_step(&timer, 3, 0);
+        events.truncate(1);
+        events.extend([TimerEvent::Paused, TimerEvent::SequenceFinished]);
+        assert_eq!(timer.handle(TimerCommand::Tick, mins(t0, 120)), events);
+
+        // Таймер остановлен в начале последовательности
+        assert!(timer.is_pause());
+        assert!(timer.is_work());
+        assert_eq!(timer.phase_index(), Some(0));
+        assert_eq!(timer.elapsed(mins(t0, 200)), Time::ZERO);
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 200)).is_empty());
+    }
+
+    #[test]
+    fn skip_last_phase_finishes_sequence() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&sequence(false), t0);
+
+        for _ in 0..3 {
+            timer.handle(TimerCommand::Skip, t0);
+        }
+        assert_eq!(timer.phase_index(), Some(3));
+        let events = timer.handle(TimerCommand::Skip, mins(t0, 1));
+        assert_eq!(events.last(), Some(&TimerEvent::SequenceFinished));
+        assert!(timer.is_pause());
+        assert_eq!(timer.phase_index(), Some(0));
+    }
+
+    #[test]
+    fn timed_pause_expires() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        assert_eq!(
+            timer.handle(TimerCommand::PauseFor(Time::from_mins(30)), mins(t0, 10)),
+            vec![TimerEvent::Paused]
+        );
+        assert_eq!(timer.pause_left(mins(t0, 20)), Some(Time::from_mins(20)));
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 39)).is_empty());
+        assert!(timer.is_pause());
+
+        // Тик опоздал, но отсчёт продолжается с конца паузы
+        assert_eq!(
+            timer.handle(TimerCommand::Tick, mins(t0, 45)),
+            vec![TimerEvent::PauseExpired]
+        );
+        assert!(!timer.is_pause());
+        assert_eq!(timer.pause_left(mins(t0, 45)), None);
+        assert_eq!(timer.elapsed(mins(t0, 45)), Time::from_mins(15));
+    }
+
+    #[test]
+    fn resume_cancels_timed_pause() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.handle(TimerCommand::PauseFor(Time::from_mins(30)), mins(t0, 10));
+        assert_eq!(
+            timer.handle(TimerCommand::Start, mins(t0, 20)),
+            vec![TimerEvent::Resumed]
+        );
+        assert_eq!(timer.pause_left(mins(t0, 20)), None);
+
+        // Обычная пауза после этого не заканчивается сама
+        timer.handle(TimerCommand::Pause, mins(t0, 30));
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 100)).is_empty());
+        assert!(timer.is_pause());
+        assert_eq!(timer.elapsed(mins(t0, 100)), Time::from_mins(20));
+    }
+
+    #[test]
+    fn stop_cancels_timed_pause() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        timer.handle(TimerCommand::PauseFor(Time::from_mins(30)), mins(t0, 10));
+        timer.handle(TimerCommand::Stop, mins(t0, 20));
+        assert_eq!(timer.pause_left(mins(t0, 20)), None);
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 100)).is_empty());
+        assert!(timer.is_pause());
+    }
+
+    #[test]
+    fn timed_pause_of_paused_timer() {
+        let t0 = Instant::now();
+        let mut timer = Timer::new(&conf(), t0);
+
+        // Таймер уже стоит на паузе до возобновления - она не становится
+        // ограниченной по времени
+        timer.handle(TimerCommand::Pause, mins(t0, 10));
+        assert!(
+            timer
+                .handle(TimerCommand::PauseFor(Time::from_mins(30)), mins(t0, 10))
+                .is_empty()
+        );
+        assert_eq!(timer.pause_left(mins(t0, 10)), None);
+        assert!(timer.handle(TimerCommand::Tick, mins(t0, 100)).is_empty());
+    }
+}
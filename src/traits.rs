@@ -2,9 +2,9 @@
 
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::fs::{self, File};
+use std::io::Write;
 use std::path::Path;
-use toml;
 
 /// Trait with functions to serialization/deserialization of the data structure.
 ///
@@ -39,7 +39,11 @@ pub trait Toml {
     }
 
     /// Serializes the structure into a TOML string and writes this string to
-    /// the specified file
+    /// the specified file.
+    ///
+    /// The string is written to a temporary file first, which then replaces
+    /// the old one: a crash in the middle of writing leaves the old file
+    /// intact
     fn write<P: AsRef<Path>>(&self, pth: P) -> Result<()>
     where
         Self: Serialize,
@@ -56,7 +60,7 @@ pub trait Toml {
             create_pth_dir(&pth)?;
         }
 
-        fs::write(&pth, content).map_err(|err| {
+        write_atomic(pth.as_ref(), &content).map_err(|err| {
             anyhow!(
                 "Ошибка записи в '{}'! Проверьте наличие файла и права доступа к нему.\n\n\
                  Код ошибки: {err}",
@@ -84,3 +88,14 @@ fn create_pth_dir<P: AsRef<Path>>(pth: P) -> Result<()> {
 
     Ok(())
 }
+
+fn write_atomic(pth: &Path, content: &str) -> std::io::Result<()> {
+    let mut tmp = pth.as_os_str().to_owned();
+    tmp.push(".tmp");
+
+    let mut file = File::create(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    // Данные должны оказаться на диске до переименования
+    file.sync_all()?;
+    fs::rename(&tmp, pth)
+}
@@ -10,6 +10,7 @@
 /***********************************************
  *                   Helpers                   *
  ***********************************************/
+mod charts;
 mod notify;
 mod utils;
 mod widget;
@@ -21,8 +22,9 @@ mod colors;
 mod update;
 mod view;
 
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
+use chrono::Local;
 use iced::{
     Event, Font, Subscription, Theme,
     advanced::graphics::image::image_rs::ImageFormat,
@@ -30,15 +32,29 @@ use iced::{
     window::{self, Settings},
 };
 
+use charts::{Chart, DashboardData, DashboardRange};
+
 use crate::{
+    aggregate::WeekStart,
     autostart::Autostart,
     conf::Config,
-    consts::{DEFAULT_FONT, PROG_LOGO, PROG_NAME},
+    consts::{DEFAULT_FONT, MAIN_WINDOW_SIZE, PROG_LOGO, PROG_NAME},
+    daily_limit::{self, DailyLimit},
+    escalation::{EscalationLevel, Escalator},
+    idle::{self, IdleBackend, IdleTracker},
+    micro_break::MicroBreaks,
     pathes,
+    pause::PauseReason,
+    profile::Profile,
     stats::Stats,
+    suggest::Suggestion,
     time::Time,
+    timer::{IntervalPolicy, Timer},
 };
 
+#[cfg(target_os = "linux")]
+use crate::logind::{self, SessionEvent};
+
 /// The main function for displaying the graphical user interface
 ///
 /// This function will independently create instances of the necessary
@@ -53,15 +69,14 @@ pub fn ui() -> iced::Result {
 
     iced::application(PROG_NAME, TimeKeeper::update, TimeKeeper::view)
         .window(Settings {
-            icon: match icon {
-                Ok(icon) => Some(icon),
-                Err(_) => None,
-            },
+            icon: icon.ok(),
             ..Default::default()
         })
+        // Перед выходом нужно сохранить статистику
+        .exit_on_close_request(false)
         .antialiasing(true)
         .centered()
-        .window_size((300., 315.))
+        .window_size(MAIN_WINDOW_SIZE)
         .resizable(false)
         .theme(TimeKeeper::theme)
         .subscription(TimeKeeper::subscription)
@@ -72,18 +87,15 @@ pub fn ui() -> iced::Result {
 
 #[derive(Debug)]
 struct TimeKeeper {
-    /// Flag indicating whether the user is currently working or not
-    is_work: bool,
+    /// Work/break cycle state machine
+    timer: Timer,
 
-    /// Flag indicating whether to increase elapsed_time
-    is_pause: bool,
+    /// Eye micro-breaks running next to the main cycle
+    micro: MicroBreaks,
 
     /// If set as `true`, the statistics will be displayed on the main page
     show_stats: bool,
 
-    /// Elapsed time (in seconds)
-    elapsed_time: u16,
-
     /// Information about run/rest times during program work
     stats: Stats,
 
@@ -101,8 +113,67 @@ struct TimeKeeper {
     /// ID of modal window
     win_id: Option<window::Id>,
 
+    /// ID of the eye micro-break countdown window
+    micro_win_id: Option<window::Id>,
+
     /// Is application autostart? (for Settings page)
     autostart: Autostart,
+
+    /// Pauses the work timer while the user is idle
+    idle: IdleTracker,
+
+    /// The moment the user left the PC (the system went to sleep or the
+    /// session was locked) and whether the timer was running then. The timer
+    /// is paused while the user is away
+    #[cfg(target_os = "linux")]
+    away_since: Option<(SystemTime, bool)>,
+
+    /// Is the user session locked now?
+    #[cfg(target_os = "linux")]
+    is_locked: bool,
+
+    /// The emergency phrase typed by the user during a strict break
+    emergency_input: String,
+
+    /// Index of the current range of the working-hours schedule
+    work_range: Option<usize>,
+
+    /// Set outside the working hours while the timer is stopped by the
+    /// schedule
+    off_hours: bool,
+
+    /// Work time since the beginning of the current working hours (in
+    /// seconds)
+    day_work: Time,
+
+    /// Warnings about the work time of the day
+    daily_limit: DailyLimit,
+
+    /// The moment the timer state was last saved
+    state_saved_at: Instant,
+
+    /// Why and since when the user has paused the timer
+    pause: Option<(PauseReason, SystemTime)>,
+
+    /// How long the user has been ignoring the current break
+    escalator: Escalator,
+
+    /// Date&time the current phase began (UNIX Timestamp). `None` -
+    /// unknown, e.g. the phase was restored after a restart
+    phase_started_at: Option<u64>,
+
+    /// How many days the dashboard shows
+    dashboard_range: DashboardRange,
+
+    /// Data of the dashboard charts
+    dashboard: DashboardData,
+
+    /// The chart under the cursor and the text of its tooltip
+    chart_hover: Option<(Chart, String)>,
+
+    /// Intervals suggested by the statistics. Computed only while the
+    /// settings page is open
+    suggestion: Option<Suggestion>,
 }
 
 impl Default for TimeKeeper {
@@ -113,25 +184,56 @@ impl Default for TimeKeeper {
 
         let (conf, is_err_create_conf) =
             utils::get_config_from_file(pathes::ProgPath::Preferences.get());
-        let stats = utils::get_stats_from_file(pathes::ProgPath::Statistics.get());
-
-        Self {
-            is_work: true,
-            is_pause: false,
+        let stats = utils::get_stats(
+            pathes::ProgPath::StatisticsDb.get(),
+            pathes::ProgPath::Statistics.get(),
+            daily_limit::day_start(Local::now(), conf.day_rollover_hour),
+        );
+
+        let mut keeper = Self {
+            timer: Timer::new(&conf, Instant::now()),
+            micro: MicroBreaks::new(&conf, Instant::now()),
             show_stats: false,
-            elapsed_time: 0,
-            wtime: Time::try_from_secs(conf.work_time).unwrap_or_default(),
-            ftime: Time::try_from_secs(conf.free_time).unwrap_or_default(),
+            wtime: conf.work_time,
+            ftime: conf.free_time,
             page: if is_err_create_conf {
                 Page::Settings
             } else {
                 Page::default()
             },
             win_id: None,
+            micro_win_id: None,
             autostart: Autostart::new(),
+            idle: IdleTracker::default(),
+            #[cfg(target_os = "linux")]
+            away_since: None,
+            #[cfg(target_os = "linux")]
+            is_locked: false,
+            emergency_input: String::new(),
+            work_range: None,
+            off_hours: false,
+            day_work: Time::ZERO,
+            daily_limit: DailyLimit::new(&conf),
+            state_saved_at: Instant::now(),
+            pause: None,
+            escalator: Escalator::default(),
+            phase_started_at: None,
+            dashboard_range: DashboardRange::default(),
+            dashboard: DashboardData::default(),
+            chart_hover: None,
+            suggestion: None,
             conf,
             stats,
+        };
+        if let Some(state) = utils::get_timer_state_from_file(pathes::ProgPath::TimerState.get()) {
+            let _ = keeper.restore_timer(&state);
         }
+        // Если программа запущена в нерабочее время, таймер сразу
+        // останавливается (задачи при остановке не нужны)
+        let _ = keeper.init_schedule();
+        keeper.refresh_suggestion();
+
+        keeper
     }
 }
 
@@ -146,6 +248,9 @@ enum Page {
 
     /// ABout program, some help info
     About,
+
+    /// Charts of the statistics
+    Dashboard,
 }
 
 #[allow(dead_code)]
@@ -154,15 +259,26 @@ enum Message {
     /// Iced event handler
     Event(Event),
 
-    /// When this message is called, the seconds counter (self.elapsed_time)
-    /// is incremented
+    /// When this message is called, the timer engine compares the wall clock
+    /// with the phase deadline and the display is refreshed
     TickTime,
     /// When you press the “Start” button, the program starts counting the
     /// elapsed time
     StartButtonPressed,
+    /// Called when the user picks a pause reason. The timer is paused (for
+    /// the time of the reason, if it has one)
+    PauseSelected(PauseReason),
     /// When the “Stop” button is pressed, the program resets the elapsed time
-    /// counter and sets `self.is_work` to the default value (`true`)
+    /// counter and switches the timer back to the work phase
     StopButtonPressed,
+    /// When the “Postpone” button is pressed, the break is postponed by the
+    /// snooze time
+    PostponeButtonPressed,
+    /// When the “Break now”/“End break” button is pressed, the current phase
+    /// ends right away and the next one begins
+    SkipButtonPressed,
+    /// When the “+5 min” button is pressed, the current phase becomes longer
+    ExtendButtonPressed,
 
     /// Called when the user clicks on the “О программе” button
     AboutButtonPressed,
@@ -177,13 +293,101 @@ enum Message {
     ShowStatsButtonPressed,
     ClearStatsButtonPressed,
     ExportCSVButtonPressed,
+    /// Called when the user clicks on the "Dashboard" button
+    DashboardButtonPressed,
+    /// Called when the user selects how many days the dashboard shows
+    DashboardRangeSelected(DashboardRange),
+    /// Called when the cursor moves to another item of a chart. The text of
+    /// the tooltip, `None` - the cursor has left the chart
+    ChartHovered(Chart, Option<String>),
 
     /// Called when the slider changes the run time
-    WTimeChanged(u16),
+    WTimeChanged(Time),
     /// Called when the slider changes the free time
-    FTimeChanged(u16),
+    FTimeChanged(Time),
+    /// Called when the user applies the suggested intervals
+    SuggestionApplied,
+    /// Called when the toggler enables or disables the weekly adjustment of
+    /// the intervals
+    AutoAdjustToggled(bool),
+    /// Called when the slider changes the lower bound of the adjusted work
+    /// time
+    AutoAdjustMinWorkChanged(Time),
+    /// Called when the slider changes the upper bound of the adjusted work
+    /// time
+    AutoAdjustMaxWorkChanged(Time),
+    /// Called when the slider changes the lower bound of the adjusted free
+    /// time
+    AutoAdjustMinBreakChanged(Time),
+    /// Called when the slider changes the upper bound of the adjusted free
+    /// time
+    AutoAdjustMaxBreakChanged(Time),
+    /// Called when the user picks what happens to the running phase when
+    /// the intervals change
+    IntervalPolicySelected(IntervalPolicy),
+    /// Called when the slider changes the long break time
+    LongBreakTimeChanged(Time),
+    /// Called when the slider changes the number of cycles between long
+    /// breaks
+    LongBreakEveryChanged(u8),
+    /// Called when the slider changes the early break warning time
+    BreakWarningChanged(Time),
+    /// Called when the slider changes the final break warning time
+    BreakFinalWarningChanged(Time),
+    /// Called when the slider changes the daily work limit
+    DailyLimitChanged(Time),
+    /// Called when the toggler enables or disables locking the screen on the
+    /// daily limit
+    DailyLimitLockToggled(bool),
+    /// Called when the slider changes the hour the day begins at
+    DayRolloverHourChanged(u8),
+    /// Called when the user selects the first day of the week
+    WeekStartSelected(WeekStart),
+    /// Called when the slider changes the snooze time
+    SnoozeTimeChanged(Time),
+    /// Called when the slider changes the maximum number of snoozes
+    MaxSnoozesChanged(u8),
+    /// Called when the toggler enables or disables eye micro-breaks
+    MicroBreaksToggled(bool),
+    /// Called when the toggler enables or disables the micro-break countdown
+    /// window
+    MicroBreakOverlayToggled(bool),
+    /// Called when the slider changes the time between micro-breaks
+    MicroBreakIntervalChanged(Time),
+    /// Called when the slider changes the micro-break time
+    MicroBreakTimeChanged(Time),
     /// Called when the checkbox toggles notification settings
     NotificationsToggled(bool),
+    /// Called when the toggler enables or disables strict mode
+    StrictModeToggled(bool),
+    /// Called when the user edits the emergency phrase in the settings
+    EmergencyPhraseChanged(String),
+    /// Called when the user types the emergency phrase during a strict break
+    EmergencyInputChanged(String),
+    /// Called when the user submits the emergency phrase
+    EmergencySkipSubmitted,
+    /// Called when the toggler enables or disables the escalation of ignored
+    /// breaks
+    EscalationToggled(bool),
+    /// Called when the slider changes the time before the critical
+    /// notification
+    EscalationCriticalChanged(Time),
+    /// Called when the slider changes the time before the break window
+    EscalationWindowChanged(Time),
+    /// Called when the slider changes the time before the screen lock
+    EscalationLockChanged(Time),
+    /// Called when the user selects another idle detection backend
+    IdleBackendSelected(IdleBackend),
+    /// Called when the slider changes the idle threshold
+    IdleThresholdChanged(Time),
+    /// Called when the toggler enables or disables the working-hours schedule
+    ScheduleToggled(bool),
+    /// Compare the wall clock with the working-hours schedule
+    ScheduleTick,
+    /// Called when the user selects another profile on the main page
+    ProfileSelected(Profile),
+    /// The idle source reported how long the user has been idle
+    IdleTime(Duration),
 
     ToggleAutostart,
 
@@ -194,6 +398,13 @@ enum Message {
     WindowOpened(window::Id),
     /// Once the break (free time) is over, close this window
     WindowClosed(window::Id),
+    /// Called when the user closes a window. Closing the main window quits
+    /// the program
+    CloseRequested(window::Id),
+
+    /// The system went to sleep/woke up or the session was (un)locked
+    #[cfg(target_os = "linux")]
+    Session(SessionEvent),
 }
 
 impl TimeKeeper {
@@ -201,16 +412,44 @@ impl TimeKeeper {
         Theme::GruvboxDark
     }
 
+    /// Is a break in strict mode going on? Such a break cannot be paused,
+    /// stopped, postponed or ended early
+    fn is_strict_break(&self) -> bool {
+        self.conf.strict_mode && !self.timer.is_work()
+    }
+
+    /// Is the modal break window shown instead of desktop notifications
+    /// (or because the break is ignored)?
+    fn has_break_window(&self) -> bool {
+        !self.conf.desktop_notifications
+            || self.conf.strict_mode
+            || self.escalator.level() >= Some(EscalationLevel::Window)
+    }
+
     fn subscription(&self) -> Subscription<Message> {
-        let mut subs = Vec::with_capacity(3);
+        let mut subs = Vec::with_capacity(6);
 
         subs.push(event::listen().map(Message::Event));
-        if !self.conf.desktop_notifications {
+        subs.push(window::close_requests().map(Message::CloseRequested));
+        if self.has_break_window() {
             subs.push(window::close_events().map(Message::WindowClosed));
         }
-        if !self.is_pause {
+        // Пауза с заданной длиной заканчивается по тику
+        if !self.timer.is_pause() || self.timer.pause_left(Instant::now()).is_some() {
             subs.push(time::every(Duration::from_secs(1)).map(|_| Message::TickTime));
         }
+        if self.conf.schedule.enabled {
+            subs.push(time::every(Duration::from_secs(30)).map(|_| Message::ScheduleTick));
+        }
+        #[cfg(target_os = "linux")]
+        subs.push(Subscription::run(logind::events).map(Message::Session));
+        if self.conf.idle_backend != IdleBackend::Disabled {
+            let (backend, cmd) = (self.conf.idle_backend, self.conf.idle_command.clone());
+            subs.push(
+                Subscription::run_with_id((backend, cmd.clone()), idle::watch(backend, cmd))
+                    .map(Message::IdleTime),
+            );
+        }
 
         Subscription::batch(subs)
     }
@@ -0,0 +1,416 @@
+//! Statistics charts drawn on the canvas (the dashboard page)
+//!
+//! Each chart reports the item under the cursor with
+//! [`Message::ChartHovered`]; the page shows it in a tooltip.
+
+use std::fmt::Display;
+
+use chrono::{Days, Local, NaiveDate};
+use iced::{
+    Color, Pixels, Point, Rectangle, Renderer, Size, Theme, alignment, mouse,
+    widget::canvas::{self, Frame, Geometry, Text, event},
+};
+
+use crate::{
+    aggregate::{self, AggregateOptions, Period, Summary},
+    conf::Config,
+    daily_limit::{self, local_day},
+    fl,
+    stats::{ALL_TIME, Stats},
+    time::{Time, TimeStyle},
+};
+
+use super::Message;
+
+const LABEL_SIZE: f32 = 10.;
+const LABEL_HEIGHT: f32 = 14.;
+
+/// How many weeks the calendar shows
+const CALENDAR_WEEKS: u64 = 26;
+
+/// Charts of the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chart {
+    Days,
+    Hours,
+    Calendar,
+}
+
+/// How many days the bar charts show
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardRange {
+    #[default]
+    Week,
+    Month,
+}
+
+impl DashboardRange {
+    pub const ALL: [Self; 2] = [Self::Week, Self::Month];
+
+    pub fn days(self) -> u64 {
+        match self {
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+}
+
+impl Display for DashboardRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", fl!("dashboard_days", days = self.days()))
+    }
+}
+
+/// Data of the charts. It is computed when the dashboard is opened or the
+/// statistics change, not on every redraw
+#[derive(Debug, Default)]
+pub struct DashboardData {
+    /// Summaries of the last days of the range
+    pub days: Vec<(NaiveDate, Summary)>,
+    /// Work time of the range by the hours of the day
+    pub hours: [Time; 24],
+    /// Work time per day for the calendar. It begins on the first day of a
+    /// week
+    pub calendar: Vec<(NaiveDate, Time)>,
+}
+
+impl DashboardData {
+    pub fn new(stats: &Stats, conf: &Config, range: DashboardRange) -> Self {
+        let opts = AggregateOptions::new(conf);
+        let now = Local::now();
+        let today = local_day(now.timestamp().max(0) as u64, opts.rollover_hour);
+        // Начало дня `day` (UNIX Timestamp) с учётом часа смены суток
+        let since = |day: NaiveDate| {
+            let ago = (today - day).num_days().max(0) as u64;
+            daily_limit::day_start(now - Days::new(ago), opts.rollover_hour)
+        };
+
+        let first = today - Days::new(range.days() - 1);
+        let summaries = aggregate::summarize(stats, Period::Day, since(first)..ALL_TIME.end, &opts);
+        let days = first
+            .iter_days()
+            .take(range.days() as usize)
+            .map(|day| (day, summaries.get(&day).copied().unwrap_or_default()))
+            .collect();
+        let hours = aggregate::hourly_work(stats, since(first)..ALL_TIME.end);
+
+        let first =
+            Period::Week.start(today, opts.week_start) - Days::new((CALENDAR_WEEKS - 1) * 7);
+        let summaries = aggregate::summarize(stats, Period::Day, since(first)..ALL_TIME.end, &opts);
+        let calendar = first
+            .iter_days()
+            .take_while(|day| *day <= today)
+            .map(|day| (day, summaries.get(&day).map(|s| s.work).unwrap_or_default()))
+            .collect();
+
+        Self {
+            days,
+            hours,
+            calendar,
+        }
+    }
+}
+
+/// Colors of the charts from the theme palette
+struct Colors {
+    work: Color,
+    breaks: Color,
+    text: Color,
+    grid: Color,
+}
+
+impl Colors {
+    fn new(theme: &Theme) -> Self {
+        let palette = theme.extended_palette();
+        Self {
+            work: palette.primary.base.color,
+            breaks: palette.success.base.color,
+            text: palette.background.base.text,
+            grid: palette.background.strong.color,
+        }
+    }
+}
+
+/// Reports the item under the cursor when it changes
+fn hover(
+    state: &mut Option<usize>,
+    item: Option<usize>,
+    chart: Chart,
+    describe: impl Fn(usize) -> String,
+) -> (event::Status, Option<Message>) {
+    if *state == item {
+        return (event::Status::Ignored, None);
+    }
+    *state = item;
+    (
+        event::Status::Ignored,
+        Some(Message::ChartHovered(chart, item.map(describe))),
+    )
+}
+
+/// Index of the bar under the cursor
+fn bar_at(bounds: Rectangle, cursor: mouse::Cursor, count: usize) -> Option<usize> {
+    let pos = cursor.position_in(bounds)?;
+    let idx = (pos.x / bounds.width * count as f32) as usize;
+    (idx < count).then_some(idx)
+}
+
+fn label(frame: &mut Frame, content: String, position: Point, color: Color) {
+    frame.fill_text(Text {
+        content,
+        position,
+        color,
+        size: Pixels(LABEL_SIZE),
+        horizontal_alignment: alignment::Horizontal::Center,
+        vertical_alignment: alignment::Vertical::Top,
+        ..Default::default()
+    });
+}
+
+/// A bar of a bar chart
+struct Bar {
+    /// Stacked parts of the bar, from the bottom
+    parts: Vec<(Time, Color)>,
+    /// Label under the bar
+    caption: Option<String>,
+}
+
+/// Bars made of stacked parts with labels under some of them
+fn draw_bars(frame: &mut Frame, colors: &Colors, bars: &[Bar], hovered: Option<usize>) {
+    let size = frame.size();
+    let height = size.height - LABEL_HEIGHT;
+    let slot = size.width / bars.len().max(1) as f32;
+    let max = bars
+        .iter()
+        .map(|bar| bar.parts.iter().map(|(time, _)| *time).sum::<Time>())
+        .max()
+        .filter(|max| !max.is_zero())
+        .unwrap_or(Time::from_secs(1));
+
+    for (idx, bar) in bars.iter().enumerate() {
+        let x = idx as f32 * slot;
+        if hovered == Some(idx) {
+            frame.fill_rectangle(Point::new(x, 0.), Size::new(slot, height), colors.grid);
+        }
+
+        // Части столбика складываются снизу вверх
+        let mut top = height;
+        for (time, color) in &bar.parts {
+            let part = time.to_secs() as f32 / max.to_secs() as f32 * height;
+            top -= part;
+            frame.fill_rectangle(
+                Point::new(x + slot * 0.15, top),
+                Size::new(slot * 0.7, part),
+                *color,
+            );
+        }
+
+        if let Some(caption) = &bar.caption {
+            let position = Point::new(x + slot / 2., height + 2.);
+            label(frame, caption.clone(), position, colors.text);
+        }
+    }
+    frame.fill_rectangle(
+        Point::new(0., height),
+        Size::new(size.width, 1.),
+        colors.grid,
+    );
+}
+
+/// Work and break time per day as stacked bars
+pub struct DayBars<'a> {
+    pub days: &'a [(NaiveDate, Summary)],
+}
+
+impl DayBars<'_> {
+    fn describe(&self, idx: usize) -> String {
+        let (day, summary) = self.days[idx];
+        fl!(
+            "chart_day_tooltip",
+            date = day.format("%d.%m").to_string(),
+            work = summary.work.format(TimeStyle::Human),
+            breaks = summary.breaks.format(TimeStyle::Human),
+            cycles = summary.cycles
+        )
+    }
+}
+
+impl canvas::Program<Message> for DayBars<'_> {
+    type State = Option<usize>;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        _event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let item = bar_at(bounds, cursor, self.days.len());
+        hover(state, item, Chart::Days, |idx| self.describe(idx))
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let colors = Colors::new(theme);
+        // За месяц подписываем только каждый седьмой день, иначе подписи
+        // наезжают друг на друга
+        let step = match self.days.len() {
+            ..=7 => 1,
+            _ => 7,
+        };
+        let bars = self
+            .days
+            .iter()
+            .enumerate()
+            .map(|(idx, (day, summary))| {
+                let parts = vec![(summary.work, colors.work), (summary.breaks, colors.breaks)];
+                let caption = (idx % step == 0).then(|| day.format("%d.%m").to_string());
+                Bar { parts, caption }
+            })
+            .collect::<Vec<_>>();
+
+        let mut frame = Frame::new(renderer, bounds.size());
+        draw_bars(&mut frame, &colors, &bars, *state);
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Work time by the hours of the day
+pub struct HourBars<'a> {
+    pub hours: &'a [Time; 24],
+}
+
+impl canvas::Program<Message> for HourBars<'_> {
+    type State = Option<usize>;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        _event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let item = bar_at(bounds, cursor, self.hours.len());
+        hover(state, item, Chart::Hours, |hour| {
+            let next = (hour + 1) % 24;
+            fl!(
+                "chart_hour_tooltip",
+                hour = hour,
+                next = next,
+                work = self.hours[hour].format(TimeStyle::Human)
+            )
+        })
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let colors = Colors::new(theme);
+        let bars = self
+            .hours
+            .iter()
+            .enumerate()
+            .map(|(hour, work)| {
+                let caption = (hour % 6 == 0).then(|| format!("{hour}:00"));
+                Bar {
+                    parts: vec![(*work, colors.work)],
+                    caption,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut frame = Frame::new(renderer, bounds.size());
+        draw_bars(&mut frame, &colors, &bars, *state);
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Work time per day as a calendar heatmap: a column is a week, the
+/// brighter the cell, the more work
+pub struct Calendar<'a> {
+    pub days: &'a [(NaiveDate, Time)],
+}
+
+impl Calendar<'_> {
+    /// Size of a cell with the gap after it
+    fn cell(&self, bounds: Rectangle) -> f32 {
+        let weeks = self.days.len().div_ceil(7).max(1) as f32;
+        (bounds.width / weeks).min(bounds.height / 7.)
+    }
+}
+
+impl canvas::Program<Message> for Calendar<'_> {
+    type State = Option<usize>;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        _event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let cell = self.cell(bounds);
+        let item = cursor
+            .position_in(bounds)
+            .map(|pos| (pos.x / cell) as usize * 7 + (pos.y / cell) as usize)
+            .filter(|&idx| idx < self.days.len());
+        hover(state, item, Chart::Calendar, |idx| {
+            let (day, work) = self.days[idx];
+            fl!(
+                "chart_calendar_tooltip",
+                date = day.format("%d.%m.%Y").to_string(),
+                work = work.format(TimeStyle::Human)
+            )
+        })
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let colors = Colors::new(theme);
+        let cell = self.cell(bounds);
+        let max = self
+            .days
+            .iter()
+            .map(|(_, work)| *work)
+            .max()
+            .filter(|max| !max.is_zero())
+            .unwrap_or(Time::from_secs(1));
+
+        let mut frame = Frame::new(renderer, bounds.size());
+        for (idx, (_, work)) in self.days.iter().enumerate() {
+            let color = match work.is_zero() {
+                true => colors.grid,
+                false => {
+                    let share = work.to_secs() as f32 / max.to_secs() as f32;
+                    colors.work.scale_alpha(0.25 + 0.75 * share)
+                }
+            };
+            let position = Point::new((idx / 7) as f32 * cell, (idx % 7) as f32 * cell);
+            frame.fill_rectangle(position, Size::new(cell - 2., cell - 2.), color);
+            if *state == Some(idx) {
+                frame.stroke(
+                    &canvas::Path::rectangle(position, Size::new(cell - 2., cell - 2.)),
+                    canvas::Stroke::default().with_color(colors.text),
+                );
+            }
+        }
+        vec![frame.into_geometry()]
+    }
+}
@@ -14,11 +14,19 @@ use winrt_notification::{Duration, Sound, Toast};
 #[cfg(unix)]
 use notify_rust::Notification;
 
-use crate::{consts::PROG_NAME, fl};
+use crate::{
+    consts::PROG_NAME, fl, pause::PauseReason, sequence::Phase, suggest::Suggestion, time::Time,
+};
 
 struct Notify {
     title: String,
     text: String,
+    /// Critical notifications stay on the screen until the user reacts. Only
+    /// the XDG notification servers have the urgency: a Windows toast is
+    /// always shown for the long duration, and notify-rust cannot set it on
+    /// macOS
+    #[cfg(all(unix, not(target_os = "macos")))]
+    critical: bool,
 }
 
 impl Notify {
@@ -30,9 +38,23 @@ impl Notify {
         Self {
             title: title.to_string(),
             text: text.to_string(),
+            #[cfg(all(unix, not(target_os = "macos")))]
+            critical: false,
         }
     }
 
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn critical(mut self) -> Self {
+        self.critical = true;
+        self
+    }
+
+    /// There is no urgency here: the notification is shown as usual
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    pub fn critical(self) -> Self {
+        self
+    }
+
     #[cfg(windows)]
     pub fn show(&self) -> Result<()> {
         use winrt_notification::IconCrop;
@@ -54,20 +76,98 @@ impl Notify {
 
     #[cfg(unix)]
     pub fn show(&self) -> Result<()> {
-        let _ = Notification::new()
+        let mut notification = Notification::new();
+        notification
             .summary(&self.title)
             .body(&self.text)
             .icon("/usr/share/icons/hicolor/scalable/apps/TimeKeeper.svg")
-            .appname("TimeKeeper")
-            .show()?;
+            .appname("TimeKeeper");
+        #[cfg(not(target_os = "macos"))]
+        if self.critical {
+            notification.urgency(notify_rust::Urgency::Critical);
+        }
+        let _ = notification.show()?;
         Ok(())
     }
 }
 
-pub fn notify_send(is_work: bool) {
-    let n_text = match is_work {
-        false => fl!("work_notification"),
-        true => fl!("break_notification"),
+/// Notifies the user about the beginning of a new phase
+pub fn notify_send(is_work: bool, is_long_break: bool) {
+    let n_text = match (is_work, is_long_break) {
+        (true, _) => fl!("work_notification"),
+        (false, false) => fl!("break_notification"),
+        (false, true) => fl!("long_break_notification"),
+    };
+    let _ = Notify::new(PROG_NAME, n_text).show();
+}
+
+/// Notifies the user about the beginning of a phase from the phase
+/// sequence
+pub fn notify_phase(phase: &Phase) {
+    let n_text = match &phase.notification {
+        Some(text) => text.clone(),
+        None => fl!("phase_notification", name = phase.name.clone()),
     };
     let _ = Notify::new(PROG_NAME, n_text).show();
 }
+
+/// Notifies the user that the phase sequence is over
+pub fn notify_sequence_finished() {
+    let _ = Notify::new(PROG_NAME, fl!("sequence_finished_notification")).show();
+}
+
+/// Notifies the user that the timed pause is over and the timer has resumed
+pub fn notify_pause_over(reason: PauseReason) {
+    let reason = reason.to_string();
+    let _ = Notify::new(PROG_NAME, fl!("pause_over_notification", reason = reason)).show();
+}
+
+/// Notifies the user that the intervals were adjusted by the statistics
+pub fn notify_intervals_adjusted(suggestion: &Suggestion) {
+    let work = suggestion.work_time.to_string_without_secs();
+    let free = suggestion.free_time.to_string_without_secs();
+    let _ = Notify::new(
+        PROG_NAME,
+        fl!("intervals_adjusted_notification", work = work, free = free),
+    )
+    .show();
+}
+
+/// Warns the user that a break begins in `left`
+pub fn notify_break_warning(is_final: bool, left: Time) {
+    let n_text = match is_final {
+        true => fl!("break_final_warning_notification", secs = left.to_secs()),
+        false => {
+            let mins = left.to_secs().div_ceil(60);
+            fl!("break_warning_notification", mins = mins)
+        }
+    };
+    let _ = Notify::new(PROG_NAME, n_text).show();
+}
+
+/// Tells the user that the work time of the day has reached `percent` of
+/// the daily limit
+pub fn notify_daily_limit(percent: u8, left: Time) {
+    let n_text = match percent {
+        100.. => fl!("daily_limit_reached_notification"),
+        _ => {
+            let mins = left.to_secs().div_ceil(60);
+            fl!("daily_limit_notification", percent = percent, mins = mins)
+        }
+    };
+    let _ = Notify::new(PROG_NAME, n_text).show();
+}
+
+/// Reminds the user to look away from the screen for `time`
+pub fn notify_micro_break(time: Time) {
+    let n_text = fl!("micro_break_notification", secs = time.to_secs());
+    let _ = Notify::new(PROG_NAME, n_text).show();
+}
+
+/// Reminds the user who keeps working during a break. The notification is
+/// critical: it does not disappear by itself
+pub fn notify_break_ignored() {
+    let _ = Notify::new(PROG_NAME, fl!("break_ignored_notification"))
+        .critical()
+        .show();
+}
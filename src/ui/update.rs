@@ -1,20 +1,45 @@
 //! Work with data; message handling
 
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::Local;
+
 use iced::{
     Event, Task, keyboard,
     window::{self, Id, Settings},
 };
 
 use crate::{
-    consts::{PROG_CRATES_URL, PROG_REPO, PROG_SITE, PROG_TELEGRAM},
-    external_cmd::open_url,
+    aggregate::{self, AggregateOptions, WeekStart},
+    consts::{
+        AUTO_ADJUST_PERIOD, DASHBOARD_WINDOW_SIZE, MAIN_WINDOW_SIZE, PHASE_EXTEND_TIME,
+        PROG_CRATES_URL, PROG_REPO, PROG_SITE, PROG_TELEGRAM, TIMER_STATE_SAVE_INTERVAL,
+    },
+    daily_limit,
+    escalation::{Escalation, EscalationLevel},
+    external_cmd::{lock_screen, open_url},
+    idle::{IdleBackend, IdleEvent},
+    micro_break::MicroBreakEvent,
     pathes::ProgPath,
-    stats::StatisticEntry,
+    pause::PauseReason,
+    profile::Profile,
+    stats::{
+        DayEndEntry, EmergencySkipEntry, IdleEntry, PauseEntry, PostponeEntry, StatisticEntry,
+    },
+    suggest::{self, AutoAdjust},
     time::{Time, get_current_date},
+    timer::{IntervalPolicy, PhaseOutcome, TimerCommand, TimerEvent, TimerState},
     traits::Toml,
 };
 
-use super::{Message, Page, TimeKeeper, notify};
+#[cfg(target_os = "linux")]
+use crate::logind::SessionEvent;
+
+use super::{
+    Message, Page, TimeKeeper,
+    charts::{Chart, DashboardData, DashboardRange},
+    notify,
+};
 
 impl TimeKeeper {
     pub fn update(&mut self, message: Message) -> Task<Message> {
@@ -24,15 +49,79 @@ impl TimeKeeper {
              ********************************************************/
             Message::TickTime => self.tick_time(),
             Message::StartButtonPressed => self.toggle_pause(),
+            Message::PauseSelected(reason) => self.pause_with(reason),
             Message::StopButtonPressed => self.set_stop(),
+            Message::PostponeButtonPressed => self.postpone(),
+            Message::SkipButtonPressed => self.skip_phase(),
+            Message::ExtendButtonPressed => self.extend_phase(),
             Message::ShowStatsButtonPressed => self.toggle_stats(),
             Message::ClearStatsButtonPressed => self.clear_stats(),
             Message::ExportCSVButtonPressed => self.gen_csv_file(),
+            Message::DashboardButtonPressed => self.select_page(Page::Dashboard),
+            Message::DashboardRangeSelected(range) => self.set_dashboard_range(range),
+            Message::ChartHovered(chart, txt) => self.set_chart_hover(chart, txt),
             Message::FTimeChanged(ftime) => self.change_ftime(ftime),
             Message::WTimeChanged(wtime) => self.change_wtime(wtime),
+            Message::SuggestionApplied => self.apply_suggestion(),
+            Message::AutoAdjustToggled(state) => self.set_auto_adjust(state),
+            Message::AutoAdjustMinWorkChanged(time) => self.change_auto_adjust_bounds(|adjust| {
+                adjust.min_work_time = time;
+                adjust.max_work_time = adjust.max_work_time.max(time);
+            }),
+            Message::AutoAdjustMaxWorkChanged(time) => self.change_auto_adjust_bounds(|adjust| {
+                adjust.max_work_time = time;
+                adjust.min_work_time = adjust.min_work_time.min(time);
+            }),
+            Message::AutoAdjustMinBreakChanged(time) => self.change_auto_adjust_bounds(|adjust| {
+                adjust.min_free_time = time;
+                adjust.max_free_time = adjust.max_free_time.max(time);
+            }),
+            Message::AutoAdjustMaxBreakChanged(time) => self.change_auto_adjust_bounds(|adjust| {
+                adjust.max_free_time = time;
+                adjust.min_free_time = adjust.min_free_time.min(time);
+            }),
+            Message::LongBreakTimeChanged(time) => self.change_long_break_time(time),
+            Message::LongBreakEveryChanged(every) => self.change_long_break_every(every),
+            Message::IntervalPolicySelected(policy) => self.set_interval_policy(policy),
+            Message::BreakWarningChanged(time) => self.change_break_warning(time),
+            Message::BreakFinalWarningChanged(time) => self.change_break_final_warning(time),
+            Message::DailyLimitChanged(limit) => self.change_daily_limit(limit),
+            Message::DailyLimitLockToggled(state) => self.set_daily_limit_lock(state),
+            Message::DayRolloverHourChanged(hour) => self.change_day_rollover_hour(hour),
+            Message::WeekStartSelected(day) => self.set_week_start(day),
+            Message::SnoozeTimeChanged(time) => self.change_snooze_time(time),
+            Message::MaxSnoozesChanged(max) => self.change_max_snoozes(max),
+            Message::MicroBreaksToggled(state) => self.set_micro_breaks(state),
+            Message::MicroBreakOverlayToggled(state) => self.set_micro_break_overlay(state),
+            Message::MicroBreakIntervalChanged(interval) => {
+                self.change_micro_break_interval(interval)
+            }
+            Message::MicroBreakTimeChanged(time) => self.change_micro_break_time(time),
             Message::NotificationsToggled(state) => self.set_notifications(state),
+            Message::StrictModeToggled(state) => self.set_strict_mode(state),
+            Message::EmergencyPhraseChanged(phrase) => self.set_emergency_phrase(phrase),
+            Message::EmergencyInputChanged(input) => self.set_emergency_input(input),
+            Message::EmergencySkipSubmitted => self.emergency_skip(),
+            Message::EscalationToggled(state) => self.set_escalation(state),
+            Message::EscalationCriticalChanged(time) => {
+                self.change_escalation(|escalation| escalation.critical_after = time)
+            }
+            Message::EscalationWindowChanged(time) => {
+                self.change_escalation(|escalation| escalation.window_after = time)
+            }
+            Message::EscalationLockChanged(time) => {
+                self.change_escalation(|escalation| escalation.lock_after = time)
+            }
+            Message::IdleBackendSelected(backend) => self.set_idle_backend(backend),
+            Message::IdleThresholdChanged(threshold) => self.set_idle_threshold(threshold),
+            Message::IdleTime(idle) => self.handle_idle_time(idle),
+            Message::ScheduleToggled(state) => self.set_schedule(state),
+            Message::ScheduleTick => self.check_schedule(),
+            Message::ProfileSelected(profile) => self.select_profile(profile),
             Message::ToggleAutostart => self.add_autostart(),
             Message::Event(event) => self.handle_events(event),
+            #[cfg(target_os = "linux")]
+            Message::Session(event) => self.handle_session_event(event),
 
             /********************************************************
              * Opening/closing program windows                      *
@@ -40,6 +129,7 @@ impl TimeKeeper {
             Message::OpenWindow => self.open_window(),
             Message::WindowOpened(id) => window::maximize(id, true),
             Message::WindowClosed(id) => self.close_window(id),
+            Message::CloseRequested(id) => self.handle_close_request(id),
 
             /********************************************************
              * Selecting pages to display                           *
@@ -58,21 +148,66 @@ impl TimeKeeper {
         }
     }
 
-    fn reset_etime(&mut self) {
-        self.elapsed_time = 0;
-    }
-
-    fn stats_push(&mut self) {
+    fn stats_push(
+        &mut self,
+        is_work: bool,
+        is_long_break: bool,
+        phase: Option<usize>,
+        elapsed: Time,
+        planned: Option<Time>,
+        outcome: PhaseOutcome,
+    ) {
+        let phase = phase
+            .and_then(|idx| self.timer.sequence_phase(idx))
+            .map(|phase| phase.name.clone())
+            .unwrap_or_default();
+        let end = get_current_date();
+        // Время начала могло остаться от фазы, которая закончилась, пока
+        // программа "спала" - тогда начало считаем по длительности
+        let by_elapsed = end.saturating_sub(elapsed.to_secs() as u64);
+        let start = self
+            .phase_started_at
+            .take()
+            .map_or(by_elapsed, |start| start.min(by_elapsed));
         self.stats.push(StatisticEntry {
-            date: get_current_date(),
-            is_wtime: self.is_work,
-            time: self.elapsed_time,
+            start,
+            end,
+            is_wtime: is_work,
+            is_long_break,
+            planned,
+            time: elapsed,
+            outcome,
+            phase,
+            profile: self.profile_name(),
+            escalation: (!is_work).then(|| self.escalator.finish()).flatten(),
         });
-        self.stats.remove_unneeded();
+        self.stats_changed();
+    }
+
+    /// Drops the outdated entries and saves the statistics right away, so
+    /// that the history survives a crash
+    fn stats_changed(&mut self) {
+        self.stats.remove_unneeded(self.day_start());
+        self.save_stats();
+        if self.page == Page::Dashboard {
+            self.refresh_dashboard();
+        }
+        self.refresh_suggestion();
+    }
+
+    /// The database saves every entry at once. Without it the statistics are
+    /// written to the TOML file
+    fn save_stats(&self) {
+        if self.stats.has_store() {
+            return;
+        }
+        if let Err(err) = self.stats.write(ProgPath::Statistics.get()) {
+            eprintln!("{err}");
+        }
     }
 
     fn close_modal_win(&mut self) -> Task<Message> {
-        if self.conf.desktop_notifications {
+        if !self.has_break_window() {
             if self.win_id.is_some() {
                 // We can use .unwrap() method of self.win_id safety
                 // because was 'is_some()' check above.
@@ -80,56 +215,564 @@ impl TimeKeeper {
             } else {
                 Task::none()
             }
+        } else if self.timer.is_work() && self.win_id.is_some() {
+            // We can use .unwrap() method of self.win_id safety
+            // because was 'is_some()' check above.
+            self.update(Message::WindowClosed(self.win_id.unwrap()))
         } else {
-            if self.is_work && self.win_id.is_some() {
-                // We can use .unwrap() method of self.win_id safety
-                // because was 'is_some()' check above.
-                self.update(Message::WindowClosed(self.win_id.unwrap()))
-            } else {
-                self.update(Message::OpenWindow)
+            self.update(Message::OpenWindow)
+        }
+    }
+
+    /// Sends a command to the timer engine and reacts to the events it
+    /// emits
+    fn timer_command(&mut self, cmd: TimerCommand) -> Task<Message> {
+        let events = self.timer.handle(cmd, Instant::now());
+        self.on_timer_events(events)
+    }
+
+    fn on_timer_events(&mut self, events: Vec<TimerEvent>) -> Task<Message> {
+        // Любое событие меняет состояние таймера - сохраняем его сразу
+        if !events.is_empty() {
+            self.save_timer_state();
+        }
+        let tasks: Vec<Task<Message>> = events
+            .into_iter()
+            .map(|event| self.on_timer_event(event))
+            .collect();
+
+        Task::batch(tasks)
+    }
+
+    /// Restores the timer state saved by the previous run. The time the
+    /// program was not running is counted too
+    pub fn restore_timer(&mut self, state: &TimerState) -> Task<Message> {
+        let gap = Duration::from_secs(get_current_date().saturating_sub(state.saved_at));
+        let events = self.timer.restore(state, gap, Instant::now());
+        let task = self.on_timer_events(events);
+        self.save_timer_state();
+
+        task
+    }
+
+    fn save_timer_state(&mut self) {
+        let now = Instant::now();
+        self.state_saved_at = now;
+
+        let state = self.timer.state(get_current_date(), now);
+        if let Err(err) = state.write(ProgPath::TimerState.get()) {
+            eprintln!("{err}");
+        }
+    }
+
+    fn on_timer_event(&mut self, event: TimerEvent) -> Task<Message> {
+        match event {
+            TimerEvent::PhaseFinished {
+                is_work,
+                is_long_break,
+                phase,
+                elapsed,
+                planned,
+                outcome,
+            } => {
+                self.stats_push(
+                    is_work,
+                    is_long_break,
+                    phase,
+                    elapsed,
+                    Some(planned),
+                    outcome,
+                );
+                if is_work {
+                    self.day_work += elapsed;
+                }
+                Task::none()
+            }
+            TimerEvent::PhaseStarted {
+                is_work,
+                is_long_break,
+                phase,
+            } => {
+                if self.conf.desktop_notifications {
+                    match phase.and_then(|idx| self.timer.sequence_phase(idx)) {
+                        Some(phase) => notify::notify_phase(phase),
+                        None => notify::notify_send(is_work, is_long_break),
+                    }
+                }
+                if is_work {
+                    self.auto_adjust();
+                    // Во время строгого перерыва новые интервалы не
+                    // применялись, чтобы перерыв нельзя было сократить
+                    self.apply_intervals();
+                    self.emergency_input.clear();
+                }
+
+                // После основного перерыва глаза уже отдохнули, а во время
+                // него микроперерывы не нужны
+                let now = Instant::now();
+                self.phase_started_at = Some(get_current_date());
+                if !is_work {
+                    self.escalator.start(now);
+                }
+                let event = self.micro.reset(now);
+                self.micro.set_suspended(!is_work, now);
+                // Фазу можно начать и во время паузы (например, "Перерыв
+                // сейчас") - тогда пауза заканчивается
+                if !self.timer.is_pause() {
+                    self.finish_pause();
+                }
+                self.on_micro_break_event(event)
+            }
+            TimerEvent::NaturalBreak { elapsed } => {
+                self.stats_push(false, false, None, elapsed, None, PhaseOutcome::Finished);
+                Task::none()
+            }
+            TimerEvent::SequenceFinished => {
+                if self.conf.desktop_notifications {
+                    notify::notify_sequence_finished();
+                }
+                self.micro.set_suspended(true, Instant::now());
+                self.escalator.finish();
+                self.idle.reset();
+                Task::none()
+            }
+            TimerEvent::Paused => {
+                self.micro.set_suspended(true, Instant::now());
+                Task::none()
+            }
+            TimerEvent::Stopped => {
+                self.finish_pause();
+                self.micro.set_suspended(true, Instant::now());
+                self.escalator.finish();
+                Task::none()
+            }
+            TimerEvent::PauseExpired => {
+                if let Some((reason, _)) = self.pause
+                    && self.conf.desktop_notifications
+                {
+                    notify::notify_pause_over(reason);
+                }
+                self.finish_pause();
+                Task::none()
+            }
+            TimerEvent::Postponed { by, .. } => {
+                // Вместо перерыва началась дополнительная работа
+                self.phase_started_at = Some(get_current_date());
+                self.stats.push_postponed(PostponeEntry {
+                    date: get_current_date(),
+                    time: by,
+                });
+                self.stats_changed();
+                Task::none()
+            }
+            TimerEvent::BreakWarning { is_final, left } => {
+                // Предупреждение показывается и при модальном окне: именно
+                // оно не даёт перерыву начаться внезапно
+                if self.conf.desktop_notifications {
+                    notify::notify_break_warning(is_final, left);
+                }
+                Task::none()
             }
+            TimerEvent::Resumed => {
+                // После остановки фаза начинается заново с запуском таймера
+                if self.timer.elapsed(Instant::now()).is_zero() {
+                    self.phase_started_at = Some(get_current_date());
+                }
+                self.finish_pause();
+                Task::none()
+            }
+            TimerEvent::Extended { .. } => Task::none(),
         }
     }
 
     fn tick_time(&mut self) -> Task<Message> {
-        self.elapsed_time += 1;
+        let task = self.timer_command(TimerCommand::Tick);
+        self.check_daily_limit();
+        if self.state_saved_at.elapsed() >= Duration::from_secs(TIMER_STATE_SAVE_INTERVAL) {
+            self.save_timer_state();
+        }
+        Task::batch([
+            task,
+            self.tick_micro_breaks(),
+            self.escalate(),
+            self.close_modal_win(),
+        ])
+    }
 
-        // В зависимости от того, что мы делаем - работаем или отдыхаем,
-        // выбираем разное время ожидания для сброса счётчика
-        let timer = match self.is_work {
-            true => self.conf.work_time,
-            false => self.conf.free_time,
-        };
+    /// Makes the reminder harder to miss while the user keeps working during
+    /// a break. The break window itself is opened by `close_modal_win`
+    fn escalate(&mut self) -> Task<Message> {
+        if self.timer.is_pause() {
+            self.escalator.suspend();
+            return Task::none();
+        }
 
-        if self.elapsed_time >= timer {
-            self.stats_push();
-            if self.conf.desktop_notifications {
-                notify::notify_send(self.is_work);
+        match self.escalator.check(&self.conf.escalation, Instant::now()) {
+            // Без уведомлений перерыв и так идёт в модальном окне
+            Some(EscalationLevel::Critical) if self.conf.desktop_notifications => {
+                notify::notify_break_ignored()
             }
-            self.is_work = !self.is_work;
-            self.reset_etime();
+            Some(EscalationLevel::Lock) => {
+                if let Err(err) = lock_screen() {
+                    eprintln!("Failed to lock the screen:\n{err}");
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// Beginning of the current day (UNIX Timestamp)
+    fn day_start(&self) -> u64 {
+        daily_limit::day_start(Local::now(), self.conf.day_rollover_hour)
+    }
+
+    /// Work time of the current day: today's work phases from the
+    /// statistics plus the running one
+    fn today_work_time(&self) -> Time {
+        let mut worked = self.stats.work_time_since(self.day_start());
+        if self.timer.is_work() {
+            worked += self.timer.elapsed(Instant::now());
+        }
+        worked
+    }
+
+    fn check_daily_limit(&mut self) {
+        let (day_start, worked) = (self.day_start(), self.today_work_time());
+        let Some(percent) = self.daily_limit.check(day_start, worked) else {
+            return;
+        };
+
+        if self.conf.desktop_notifications {
+            notify::notify_daily_limit(percent, self.daily_limit.limit() - worked);
         }
 
-        self.close_modal_win()
+        if percent >= 100
+            && self.conf.daily_limit_lock
+            && let Err(err) = lock_screen()
+        {
+            eprintln!("Failed to lock the screen:\n{err}");
+        }
+    }
+
+    /// Eye micro-breaks are counted only while the user is working
+    fn tick_micro_breaks(&mut self) -> Task<Message> {
+        let now = Instant::now();
+        let is_active = self.conf.micro_breaks && self.timer.is_work() && !self.timer.is_pause();
+
+        self.micro.set_suspended(!is_active, now);
+        let event = self.micro.tick(now);
+        self.on_micro_break_event(event)
+    }
+
+    fn on_micro_break_event(&mut self, event: Option<MicroBreakEvent>) -> Task<Message> {
+        match event {
+            Some(MicroBreakEvent::Started) => {
+                if self.conf.desktop_notifications {
+                    notify::notify_micro_break(self.conf.micro_break_time);
+                }
+                self.open_micro_window()
+            }
+            Some(MicroBreakEvent::Finished) => self.close_micro_window(),
+            None => Task::none(),
+        }
     }
 
     fn toggle_pause(&mut self) -> Task<Message> {
-        self.is_pause = !self.is_pause;
-        Task::none()
+        if self.is_strict_break() {
+            return Task::none();
+        }
+        // Пользователь может работать и в нерабочее время
+        self.off_hours = false;
+        match self.timer.is_pause() {
+            true => {
+                // Пользователь сам управляет таймером - автоматическая пауза
+                // больше не действует
+                self.idle.reset();
+                self.timer_command(TimerCommand::Start)
+            }
+            false => self.pause_with(PauseReason::Manual),
+        }
+    }
+
+    fn pause_with(&mut self, reason: PauseReason) -> Task<Message> {
+        if self.is_strict_break() || self.timer.is_pause() {
+            return Task::none();
+        }
+        self.idle.reset();
+        self.pause = Some((reason, SystemTime::now()));
+
+        match reason.duration() {
+            Some(len) => self.timer_command(TimerCommand::PauseFor(len)),
+            None => self.timer_command(TimerCommand::Pause),
+        }
+    }
+
+    /// The pause made by the user is over: it is saved to the statistics
+    /// with its reason
+    fn finish_pause(&mut self) {
+        let Some((reason, since)) = self.pause.take() else {
+            return;
+        };
+        let elapsed = SystemTime::now().duration_since(since).unwrap_or_default();
+        self.stats.push_pause(PauseEntry {
+            date: get_current_date(),
+            time: elapsed.into(),
+            reason,
+        });
+        self.stats_changed();
     }
 
     fn set_stop(&mut self) -> Task<Message> {
-        /* После того, как пользователь нажмёт на "Стоп", нам нужно сбросить
-         * таймер, после чего установить рабочее время (а не время отдыха) и
-         * поставить счётчик (таймер) на паузу.
-         */
-        self.reset_etime();
-        self.is_work = true;
-        self.is_pause = true;
+        if self.is_strict_break() {
+            return Task::none();
+        }
+        self.idle.reset();
+        self.timer_command(TimerCommand::Stop)
+    }
 
+    fn postpone(&mut self) -> Task<Message> {
+        if self.is_strict_break() {
+            return Task::none();
+        }
+        self.timer_command(TimerCommand::Postpone)
+    }
+
+    fn skip_phase(&mut self) -> Task<Message> {
+        if self.is_strict_break() {
+            return Task::none();
+        }
+        self.idle.reset();
+        self.timer_command(TimerCommand::Skip)
+    }
+
+    fn extend_phase(&mut self) -> Task<Message> {
+        self.timer_command(TimerCommand::Extend(PHASE_EXTEND_TIME))
+    }
+
+    fn set_emergency_input(&mut self, input: String) -> Task<Message> {
+        self.emergency_input = input;
         Task::none()
     }
 
+    /// The only way out of a strict break: the user has typed the emergency
+    /// phrase. Every such skip is saved to the statistics
+    fn emergency_skip(&mut self) -> Task<Message> {
+        let phrase = self.conf.emergency_phrase.trim();
+        if !self.is_strict_break() || phrase.is_empty() || self.emergency_input.trim() != phrase {
+            return Task::none();
+        }
+
+        self.stats.push_emergency_skip(EmergencySkipEntry {
+            date: get_current_date(),
+            time: self.timer.remaining_time(Instant::now()),
+        });
+        self.stats_changed();
+        self.emergency_input.clear();
+        self.timer_command(TimerCommand::Skip)
+    }
+
+    /// Gives the timer the work/break durations of the active profile and
+    /// of the current schedule range
+    fn apply_intervals(&mut self) {
+        let mut conf = self.conf.clone();
+        if let Some(profile) = self.conf.find_profile(&self.conf.profile) {
+            profile.apply(&mut conf);
+        }
+        if let Some(range) = self
+            .work_range
+            .and_then(|idx| self.conf.schedule.ranges.get(idx))
+        {
+            range.apply(&mut conf);
+        }
+        self.timer.set_intervals(&conf, Instant::now());
+    }
+
+    /// Name of the profile the timer works by: the profile of the current
+    /// schedule range or the active one. Empty - the intervals from the
+    /// settings
+    fn profile_name(&self) -> String {
+        let range_profile = self
+            .work_range
+            .and_then(|idx| self.conf.schedule.ranges.get(idx))
+            .and_then(|range| range.profile.as_deref());
+
+        [range_profile, Some(self.conf.profile.as_str())]
+            .into_iter()
+            .flatten()
+            .find_map(|name| self.conf.find_profile(name))
+            .map(|profile| profile.name)
+            .unwrap_or_default()
+    }
+
+    fn select_profile(&mut self, profile: Profile) -> Task<Message> {
+        // Сменой профиля нельзя сократить строгий перерыв
+        if self.is_strict_break() {
+            return Task::none();
+        }
+        self.conf.profile = profile.name;
+        self.apply_intervals();
+
+        if let Err(err) = self.conf.write(ProgPath::Preferences.get()) {
+            eprintln!("{err}");
+        }
+        Task::none()
+    }
+
+    /// Finds the current range of the schedule without starting or ending
+    /// the working hours. Outside them the timer is stopped
+    pub fn init_schedule(&mut self) -> Task<Message> {
+        self.work_range = self.conf.schedule.active(Local::now().naive_local());
+        self.off_hours = self.conf.schedule.enabled && self.work_range.is_none();
+        self.apply_intervals();
+
+        match self.off_hours {
+            true => self.timer_command(TimerCommand::Stop),
+            false => Task::none(),
+        }
+    }
+
+    fn check_schedule(&mut self) -> Task<Message> {
+        let range = self.conf.schedule.active(Local::now().naive_local());
+        if range == self.work_range {
+            return Task::none();
+        }
+
+        match std::mem::replace(&mut self.work_range, range) {
+            None => self.begin_work_day(),
+            Some(_) if range.is_none() => self.end_work_day(),
+            // Один рабочий диапазон сменился другим
+            Some(_) => {
+                self.apply_intervals();
+                Task::none()
+            }
+        }
+    }
+
+    /// The working hours have begun: the work phase starts from the
+    /// beginning
+    fn begin_work_day(&mut self) -> Task<Message> {
+        self.off_hours = false;
+        self.idle.reset();
+        self.day_work = Time::ZERO;
+        self.apply_intervals();
+
+        let stop = self.timer_command(TimerCommand::Stop);
+        let start = self.timer_command(TimerCommand::Start);
+        Task::batch([stop, start])
+    }
+
+    /// The working hours are over: the unfinished phase and the work time
+    /// of the day are saved to the statistics, and the timer is stopped
+    fn end_work_day(&mut self) -> Task<Message> {
+        // Остановленный таймер сам сохраняет незаконченную фазу
+        let stop = self.timer_command(TimerCommand::Stop);
+        self.stats.push_day_end(DayEndEntry {
+            date: get_current_date(),
+            time: self.day_work,
+        });
+        self.stats_changed();
+
+        self.off_hours = true;
+        self.idle.reset();
+        self.day_work = Time::ZERO;
+        self.apply_intervals();
+
+        Task::batch([stop, self.close_modal_win(), self.close_micro_window()])
+    }
+
+    fn set_schedule(&mut self, state: bool) -> Task<Message> {
+        self.conf.schedule.enabled = state;
+        let task = match state {
+            true => self.init_schedule(),
+            false => {
+                self.work_range = None;
+                self.off_hours = false;
+                Task::none()
+            }
+        };
+        Task::batch([task, self.save_settings()])
+    }
+
+    /// Pauses the work timer when the user has been idle for too long and
+    /// resumes it when the user is back. The idle period is saved to the
+    /// statistics separately
+    fn handle_idle_time(&mut self, idle: Duration) -> Task<Message> {
+        self.escalator.set_idle(Some(idle));
+        let threshold = self.conf.idle_threshold.to_duration();
+
+        let is_working = self.timer.is_work() && !self.timer.is_pause();
+
+        match self
+            .idle
+            .check(idle, threshold, is_working, SystemTime::now())
+        {
+            Some(IdleEvent::Away) => self.timer_command(TimerCommand::Pause),
+            Some(IdleEvent::Back(elapsed)) => {
+                self.stats.push_idle(IdleEntry {
+                    date: get_current_date(),
+                    time: elapsed.into(),
+                });
+                self.stats_changed();
+                self.timer_command(TimerCommand::Start)
+            }
+            None => Task::none(),
+        }
+    }
+
+    /// Sleep and screen lock are counted as a natural break: if the user was
+    /// away for at least the break time, the work phase starts anew
+    #[cfg(target_os = "linux")]
+    fn handle_session_event(&mut self, event: SessionEvent) -> Task<Message> {
+        match event {
+            SessionEvent::Sleep => self.set_away(),
+            SessionEvent::Lock => {
+                self.is_locked = true;
+                self.set_away()
+            }
+            SessionEvent::Unlock => {
+                self.is_locked = false;
+                self.set_back()
+            }
+            // Если после пробуждения сессия всё ещё заблокирована, то
+            // пользователь вернётся только после разблокировки
+            SessionEvent::Wake if self.is_locked => Task::none(),
+            SessionEvent::Wake => self.set_back(),
+        }
+    }
+
+    /// The timer is paused while the user is away, so the phases do not
+    /// change behind the locked screen
+    #[cfg(target_os = "linux")]
+    fn set_away(&mut self) -> Task<Message> {
+        if self.away_since.is_some() {
+            return Task::none();
+        }
+        let is_running = !self.timer.is_pause();
+        self.away_since = Some((SystemTime::now(), is_running));
+        match is_running {
+            true => self.timer_command(TimerCommand::Pause),
+            false => Task::none(),
+        }
+    }
+
+    /// The time away is settled with the timer, then the timer resumes if it
+    /// was running before
+    #[cfg(target_os = "linux")]
+    fn set_back(&mut self) -> Task<Message> {
+        let Some((since, was_running)) = self.away_since.take() else {
+            return Task::none();
+        };
+        // SystemTime, в отличие от Instant, идёт и во время сна системы
+        let away = SystemTime::now().duration_since(since).unwrap_or_default();
+        let away = self.timer_command(TimerCommand::Away(away));
+        let start = match was_running {
+            true => self.timer_command(TimerCommand::Start),
+            false => Task::none(),
+        };
+        Task::batch([away, start])
+    }
+
     /// Handle events (e.g. keyboard combinations)
     ///
     /// - F1 - about
@@ -138,6 +781,10 @@ impl TimeKeeper {
     /// - F4 - show/hide statistics
     /// - F5 - start/pause
     /// - F6 - stop
+    /// - F7 - postpone the break
+    /// - F8 - take a break now/end the break and resume work
+    /// - F9 - make the current phase 5 minutes longer
+    /// - F10 - dashboard
     fn handle_events(&mut self, event: Event) -> Task<Message> {
         match event {
             Event::Keyboard(keyboard::Event::KeyPressed {
@@ -164,27 +811,91 @@ impl TimeKeeper {
                 key: keyboard::Key::Named(keyboard::key::Named::F6),
                 ..
             }) => self.set_stop(),
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::F7),
+                ..
+            }) => self.postpone(),
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::F8),
+                ..
+            }) => self.skip_phase(),
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::F9),
+                ..
+            }) => self.extend_phase(),
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::F10),
+                ..
+            }) => self.select_page(Page::Dashboard),
             _ => Task::none(),
         }
     }
 
     fn select_page(&mut self, page: Page) -> Task<Message> {
+        let was_dashboard = self.page == Page::Dashboard;
         if self.page == page {
             self.page = Page::default();
         } else {
             self.page = page;
         }
+
+        let is_dashboard = self.page == Page::Dashboard;
+        if is_dashboard {
+            self.refresh_dashboard();
+        }
+        self.refresh_suggestion();
+        if was_dashboard == is_dashboard {
+            return Task::none();
+        }
+        // Графики не помещаются в обычное окно, поэтому на время просмотра
+        // оно увеличивается
+        self.chart_hover = None;
+        let size = match is_dashboard {
+            true => DASHBOARD_WINDOW_SIZE,
+            false => MAIN_WINDOW_SIZE,
+        };
+        window::get_oldest().and_then(move |id| window::resize(id, size.into()))
+    }
+
+    fn refresh_dashboard(&mut self) {
+        self.dashboard = DashboardData::new(&self.stats, &self.conf, self.dashboard_range);
+    }
+
+    fn set_dashboard_range(&mut self, range: DashboardRange) -> Task<Message> {
+        self.dashboard_range = range;
+        self.refresh_dashboard();
+        Task::none()
+    }
+
+    fn set_chart_hover(&mut self, chart: Chart, txt: Option<String>) -> Task<Message> {
+        match txt {
+            Some(txt) => self.chart_hover = Some((chart, txt)),
+            // Курсор мог уже перейти на другой график, тогда его подсказку
+            // не трогаем
+            None if self.chart_hover.as_ref().is_some_and(|(c, _)| *c == chart) => {
+                self.chart_hover = None
+            }
+            None => {}
+        }
         Task::none()
     }
 
     fn save_settings(&mut self) -> Task<Message> {
         if self.page == Page::Settings {
-            self.conf.work_time = self.wtime.to_secs();
-            self.conf.free_time = self.ftime.to_secs();
+            self.conf.work_time = self.wtime;
+            self.conf.free_time = self.ftime;
+            // Строгий перерыв нельзя сократить через настройки: интервалы
+            // применятся, когда начнётся работа
+            if !self.is_strict_break() {
+                self.apply_intervals();
+            }
+            self.micro.set_intervals(&self.conf);
+            self.daily_limit.set_limit(&self.conf);
 
             if let Err(err) = self.conf.write(ProgPath::Preferences.get()) {
                 eprintln!("{err}");
             }
+            self.refresh_suggestion();
         }
 
         Task::none()
@@ -196,7 +907,8 @@ impl TimeKeeper {
     }
 
     fn clear_stats(&mut self) -> Task<Message> {
-        self.stats.stats.clear();
+        self.stats.clear();
+        self.stats_changed();
         Task::none()
     }
 
@@ -204,16 +916,161 @@ impl TimeKeeper {
         let csv = self.stats.gen_csv();
         let _ = std::fs::write(ProgPath::CSVFile.get(), csv);
 
+        let opts = AggregateOptions::new(&self.conf);
+        let summary = aggregate::gen_summary_csv(&self.stats, &opts);
+        let _ = std::fs::write(ProgPath::SummaryCSVFile.get(), summary);
+
         Task::none()
     }
 
-    fn change_ftime(&mut self, ftime: u16) -> Task<Message> {
-        self.ftime = Time::from_secs(ftime);
+    fn change_ftime(&mut self, ftime: Time) -> Task<Message> {
+        self.ftime = ftime;
+        self.save_settings()
+    }
+
+    fn change_wtime(&mut self, wtime: Time) -> Task<Message> {
+        self.wtime = wtime;
+        self.save_settings()
+    }
+
+    fn apply_suggestion(&mut self) -> Task<Message> {
+        if let Some(suggestion) = &self.suggestion {
+            self.wtime = suggestion.work_time;
+            self.ftime = suggestion.free_time;
+        }
+        self.save_settings()
+    }
+
+    /// The suggestion is read from the database, so it is computed when the
+    /// settings page is opened or its data change, not on every redraw
+    pub fn refresh_suggestion(&mut self) {
+        self.suggestion = match self.page {
+            Page::Settings => suggest::suggest(&self.stats, &self.conf),
+            _ => None,
+        };
+    }
+
+    fn set_auto_adjust(&mut self, state: bool) -> Task<Message> {
+        self.conf.auto_adjust.enabled = state;
+        // Первая подстройка - через неделю после включения
+        if state {
+            self.conf.auto_adjust.last_adjusted = get_current_date();
+        }
+        self.save_settings()
+    }
+
+    /// The lower bound never goes above the upper one and vice versa
+    fn change_auto_adjust_bounds(&mut self, change: impl FnOnce(&mut AutoAdjust)) -> Task<Message> {
+        change(&mut self.conf.auto_adjust);
+        self.save_settings()
+    }
+
+    /// Applies the suggested intervals (within the user bounds) once a week
+    /// if the automatic adjustment is enabled
+    fn auto_adjust(&mut self) {
+        let now = get_current_date();
+        let adjust = &self.conf.auto_adjust;
+        if !adjust.enabled || now < adjust.last_adjusted + AUTO_ADJUST_PERIOD {
+            return;
+        }
+        self.conf.auto_adjust.last_adjusted = now;
+
+        if let Some(mut suggestion) = suggest::suggest(&self.stats, &self.conf) {
+            self.conf.auto_adjust.clamp(&mut suggestion);
+            self.conf.work_time = suggestion.work_time;
+            self.conf.free_time = suggestion.free_time;
+            self.wtime = suggestion.work_time;
+            self.ftime = suggestion.free_time;
+
+            if self.conf.desktop_notifications {
+                notify::notify_intervals_adjusted(&suggestion);
+            }
+        }
+        if let Err(err) = self.conf.write(ProgPath::Preferences.get()) {
+            eprintln!("{err}");
+        }
+        self.refresh_suggestion();
+    }
+
+    fn set_interval_policy(&mut self, policy: IntervalPolicy) -> Task<Message> {
+        self.conf.interval_policy = policy;
+        self.save_settings()
+    }
+
+    fn change_long_break_time(&mut self, time: Time) -> Task<Message> {
+        self.conf.long_break_time = time;
+        self.save_settings()
+    }
+
+    fn change_long_break_every(&mut self, every: u8) -> Task<Message> {
+        self.conf.long_break_every = every;
+        self.save_settings()
+    }
+
+    fn change_break_warning(&mut self, time: Time) -> Task<Message> {
+        self.conf.break_warning = time;
+        self.save_settings()
+    }
+
+    fn change_break_final_warning(&mut self, time: Time) -> Task<Message> {
+        self.conf.break_final_warning = time;
+        self.save_settings()
+    }
+
+    fn change_daily_limit(&mut self, limit: Time) -> Task<Message> {
+        self.conf.daily_limit = limit;
+        self.save_settings()
+    }
+
+    fn set_daily_limit_lock(&mut self, state: bool) -> Task<Message> {
+        self.conf.daily_limit_lock = state;
+        self.save_settings()
+    }
+
+    fn change_day_rollover_hour(&mut self, hour: u8) -> Task<Message> {
+        self.conf.day_rollover_hour = hour;
+        self.save_settings()
+    }
+
+    fn set_week_start(&mut self, day: WeekStart) -> Task<Message> {
+        self.conf.week_start = day;
+        self.save_settings()
+    }
+
+    fn change_snooze_time(&mut self, time: Time) -> Task<Message> {
+        self.conf.snooze_time = time;
+        self.save_settings()
+    }
+
+    fn change_max_snoozes(&mut self, max: u8) -> Task<Message> {
+        self.conf.max_snoozes = max;
+        self.save_settings()
+    }
+
+    fn set_micro_breaks(&mut self, state: bool) -> Task<Message> {
+        self.conf.micro_breaks = state;
+
+        // Если микроперерывы выключили прямо во время микроперерыва, его
+        // нужно завершить
+        let event = match state {
+            true => None,
+            false => self.micro.reset(Instant::now()),
+        };
+        Task::batch([self.on_micro_break_event(event), self.save_settings()])
+    }
+
+    fn set_micro_break_overlay(&mut self, state: bool) -> Task<Message> {
+        self.conf.micro_break_overlay = state;
         self.save_settings()
     }
 
-    fn change_wtime(&mut self, wtime: u16) -> Task<Message> {
-        self.wtime = Time::from_secs(wtime);
+    fn change_micro_break_interval(&mut self, interval: Time) -> Task<Message> {
+        self.conf.micro_break_interval = interval;
+        self.save_settings()
+    }
+
+    fn change_micro_break_time(&mut self, time: Time) -> Task<Message> {
+        self.conf.micro_break_time = time;
         self.save_settings()
     }
 
@@ -222,8 +1079,47 @@ impl TimeKeeper {
         self.save_settings()
     }
 
+    fn set_strict_mode(&mut self, state: bool) -> Task<Message> {
+        // Выключить строгий режим можно только после окончания перерыва
+        if self.is_strict_break() {
+            return Task::none();
+        }
+        self.conf.strict_mode = state;
+        self.save_settings()
+    }
+
+    fn set_emergency_phrase(&mut self, phrase: String) -> Task<Message> {
+        if self.is_strict_break() {
+            return Task::none();
+        }
+        self.conf.emergency_phrase = phrase;
+        self.save_settings()
+    }
+
+    fn set_escalation(&mut self, state: bool) -> Task<Message> {
+        self.conf.escalation.enabled = state;
+        self.save_settings()
+    }
+
+    fn change_escalation(&mut self, change: impl FnOnce(&mut Escalation)) -> Task<Message> {
+        change(&mut self.conf.escalation);
+        self.save_settings()
+    }
+
+    fn set_idle_backend(&mut self, backend: IdleBackend) -> Task<Message> {
+        // Новый источник ещё ничего не сообщил
+        self.escalator.set_idle(None);
+        self.conf.idle_backend = backend;
+        self.save_settings()
+    }
+
+    fn set_idle_threshold(&mut self, threshold: Time) -> Task<Message> {
+        self.conf.idle_threshold = threshold;
+        self.save_settings()
+    }
+
     fn open_window(&mut self) -> Task<Message> {
-        if !self.is_work && self.win_id.is_none() {
+        if !self.timer.is_work() && self.win_id.is_none() {
             let win_settings = Settings {
                 size: iced::Size::from((500., 500.)),
                 position: window::Position::Centered,
@@ -241,11 +1137,55 @@ impl TimeKeeper {
         }
     }
 
+    /// Closing the main window quits the program: the statistics and the
+    /// timer state are saved first. Break windows cannot be closed this way
+    fn handle_close_request(&mut self, id: Id) -> Task<Message> {
+        if self.win_id == Some(id) || self.micro_win_id == Some(id) {
+            return Task::none();
+        }
+
+        self.save_stats();
+        self.save_timer_state();
+        iced::exit()
+    }
+
     fn close_window(&mut self, id: Id) -> Task<Message> {
-        self.win_id = None;
+        if self.micro_win_id == Some(id) {
+            self.micro_win_id = None;
+        } else {
+            self.win_id = None;
+        }
         window::close(id)
     }
 
+    /// Opens a small countdown window on top of all windows for the time of
+    /// an eye micro-break
+    fn open_micro_window(&mut self) -> Task<Message> {
+        if !self.conf.micro_break_overlay || self.micro_win_id.is_some() {
+            return Task::none();
+        }
+
+        let win_settings = Settings {
+            size: iced::Size::from((250., 120.)),
+            position: window::Position::Centered,
+            resizable: false,
+            decorations: false,
+            level: window::Level::AlwaysOnTop,
+            exit_on_close_request: false,
+            ..Default::default()
+        };
+        let (id, task) = window::open(win_settings);
+        self.micro_win_id = Some(id);
+        task.discard()
+    }
+
+    fn close_micro_window(&mut self) -> Task<Message> {
+        match self.micro_win_id {
+            Some(id) => self.close_window(id),
+            None => Task::none(),
+        }
+    }
+
     fn add_autostart(&mut self) -> Task<Message> {
         let _ = if self.autostart.is_autostart() {
             self.autostart.remove_autostart()
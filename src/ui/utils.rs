@@ -1,8 +1,15 @@
 //! Some utils and helpers
 
-use crate::{conf::Config, stats::Stats, traits::Toml};
+use crate::{
+    conf::Config, stats::Stats, stats_store::StatsStore, time::get_current_date, timer::TimerState,
+    traits::Toml,
+};
+use anyhow::Result;
 use iced::{Color, Theme, widget::container};
-use std::path::Path;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use super::colors::BACKGROUND_COLOR_IN_FREETIME;
 
@@ -18,25 +25,83 @@ pub fn get_container_style(style: &Theme, is_work: bool) -> container::Style {
     }
 }
 
+/// Container with the background colour of a phase from the phase sequence
+pub fn get_colored_container_style(color: Color) -> container::Style {
+    container::Style {
+        background: Some(iced::Background::Color(color)),
+        ..Default::default()
+    }
+}
+
 pub fn get_dimmed_text_color(style: &Theme) -> Color {
     style.palette().text.scale_alpha(0.5)
 }
 
+/// Opens the statistics database and imports `legacy` (the old TOML file)
+/// into it on the first run. If the database cannot be opened, the
+/// statistics are read from `legacy`
+pub fn get_stats<P, L>(db: P, legacy: L, keep_since: u64) -> Stats
+where
+    P: AsRef<Path>,
+    L: AsRef<Path>,
+{
+    let stats = StatsStore::open(db).and_then(|mut store| {
+        if !store.is_imported()? {
+            store.import(&get_stats_from_file(&legacy))?;
+        }
+        Stats::with_store(store, keep_since)
+    });
+    match stats {
+        Ok(stats) => stats,
+        Err(why) => {
+            eprintln!("Failed to open statistics database:\n{why}");
+            eprintln!("Using the statistics file...");
+            get_stats_from_file(legacy)
+        }
+    }
+}
+
 pub fn get_stats_from_file<P: AsRef<Path>>(file: P) -> Stats {
+    let file = file.as_ref();
     let stats = Stats::parse(file);
     match stats {
-        Ok(stats) => stats,
+        Ok(mut stats) => {
+            stats.migrate();
+            stats
+        }
         Err(why) => {
             eprintln!("Failed to parse statistics file:\n{why}");
+            // Файл есть, но не разбирается (или даже не читается как текст) -
+            // он повреждён. Откладываем его в сторону, чтобы пустая
+            // статистика не затёрла историю
+            if file.exists() {
+                match move_aside(file) {
+                    Ok(pth) => eprintln!("The damaged file was moved to '{}'", pth.display()),
+                    Err(err) => eprintln!("Failed to move the damaged file aside:\n{err}"),
+                }
+            }
             eprintln!("Using the empty value...");
             Stats::default()
         }
     }
 }
 
+/// Renames a damaged file to `<name>.corrupt-<UNIX Timestamp>` and returns
+/// the new path
+fn move_aside(file: &Path) -> Result<PathBuf> {
+    let mut aside = file.as_os_str().to_owned();
+    aside.push(format!(".corrupt-{}", get_current_date()));
+    let aside = PathBuf::from(aside);
+
+    fs::rename(file, &aside)?;
+    Ok(aside)
+}
+
 pub fn get_config_from_file<P: AsRef<Path>>(file: P) -> (Config, bool) {
     let mut is_err_create_conf = false;
-    let conf = Config::parse(file);
+    // Нулевые интервалы не разбираются как ошибка TOML, но таймер с ними
+    // работать не может
+    let conf = Config::parse(file).and_then(|conf| conf.validate().map(|()| conf));
     let conf = match conf {
         Ok(conf) => conf,
         Err(why) => {
@@ -49,3 +114,55 @@ pub fn get_config_from_file<P: AsRef<Path>>(file: P) -> (Config, bool) {
 
     (conf, is_err_create_conf)
 }
+
+/// Reads the timer state saved by the previous run. Returns `None` if there
+/// is no saved state
+pub fn get_timer_state_from_file<P: AsRef<Path>>(file: P) -> Option<TimerState> {
+    if !file.as_ref().exists() {
+        return None;
+    }
+
+    match TimerState::parse(file) {
+        Ok(state) => Some(state),
+        Err(why) => {
+            eprintln!("Failed to parse timer state:\n{why}");
+            eprintln!("Starting a new work phase...");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damaged_stats_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("time_keeper-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("stat.toml");
+
+        // Не UTF-8: файл даже не читается как строка
+        fs::write(&file, [0xff, 0xfe, 0x00, 0x80]).unwrap();
+        let stats = get_stats_from_file(&file);
+        assert_eq!(stats.len(), 0);
+        assert!(!file.exists());
+        let moved = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("stat.toml.corrupt-")
+            })
+            .count();
+        assert_eq!(moved, 1);
+
+        // Отсутствующий файл - не повреждение
+        get_stats_from_file(dir.join("missing.toml"));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 //! Interface rendering
 
 mod about;
+mod dashboard;
 mod main;
 mod settings;
 
@@ -17,11 +18,12 @@ const SMALL_TEXT_SIZE: u16 = 12;
 // const SMALL_TEXT_SIZE: u16 = 11;
 
 impl TimeKeeper {
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         match self.page {
             Page::Main => self.main_page(),
             Page::Settings => self.settings_page(),
             Page::About => self.about_page(),
+            Page::Dashboard => self.dashboard_page(),
         }
     }
 }
@@ -22,7 +22,7 @@ use iced::{
 use super::SMALL_TEXT_SIZE;
 
 impl TimeKeeper {
-    pub fn about_page(&self) -> Element<Message> {
+    pub fn about_page(&self) -> Element<'_, Message> {
         let about_devs = column![
             column![
                 row![text(fl!("about_idea")), horizontal_rule(0)]
@@ -78,7 +78,7 @@ impl TimeKeeper {
             .height(64)
     }
 
-    fn get_header(&self) -> Column<Message> {
+    fn get_header(&self) -> Column<'_, Message> {
         let mut version = String::with_capacity(10);
         version.push_str(fl!("about_version").as_str());
         version.push(' ');
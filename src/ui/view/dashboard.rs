@@ -0,0 +1,86 @@
+//! Dashboard page: charts of the statistics
+
+use crate::fl;
+use crate::ui::{
+    Message, TimeKeeper,
+    charts::{Calendar, Chart, DashboardRange, DayBars, HourBars},
+    widget::{header, txt_tooltip},
+};
+
+use iced::{
+    Alignment::Center,
+    Element, Length,
+    widget::{
+        Canvas, button, canvas, column, container, horizontal_space, row, scrollable, text,
+        tooltip::Position,
+    },
+};
+
+use super::SMALL_TEXT_SIZE;
+
+impl TimeKeeper {
+    pub fn dashboard_page(&self) -> Element<'_, Message> {
+        let header = header(fl!("dashboard"));
+
+        let ranges = DashboardRange::ALL.iter().fold(
+            row![text(fl!("dashboard_range")), horizontal_space()]
+                .spacing(5)
+                .align_y(Center),
+            |row, &range| {
+                let style = match range == self.dashboard_range {
+                    true => button::primary,
+                    false => button::secondary,
+                };
+                row.push(
+                    button(text(range.to_string()).size(SMALL_TEXT_SIZE))
+                        .style(style)
+                        .padding(3)
+                        .on_press(Message::DashboardRangeSelected(range)),
+                )
+            },
+        );
+
+        let days = DayBars {
+            days: &self.dashboard.days,
+        };
+        let hours = HourBars {
+            hours: &self.dashboard.hours,
+        };
+        let calendar = Calendar {
+            days: &self.dashboard.calendar,
+        };
+        let charts = column![
+            text(fl!("chart_days")),
+            self.chart(Chart::Days, canvas(days).height(140)),
+            text(fl!("chart_hours")),
+            self.chart(Chart::Hours, canvas(hours).height(90)),
+            text(fl!("chart_calendar")),
+            self.chart(Chart::Calendar, canvas(calendar).height(90)),
+        ]
+        .spacing(5);
+
+        let layout = column![
+            header,
+            ranges,
+            scrollable(charts).spacing(5).height(Length::Fill),
+            button(text(fl!("pref_close"))).on_press(Message::DashboardButtonPressed),
+        ]
+        .spacing(5);
+
+        container(layout).padding(10).into()
+    }
+
+    /// The chart with the tooltip about the item under the cursor
+    fn chart<'a, P>(&'a self, chart: Chart, canvas: Canvas<P, Message>) -> Element<'a, Message>
+    where
+        P: canvas::Program<Message> + 'a,
+    {
+        let canvas = canvas.width(Length::Fill);
+        match &self.chart_hover {
+            Some((hovered, txt)) if *hovered == chart => {
+                txt_tooltip(canvas, txt.as_str(), Position::FollowCursor).into()
+            }
+            _ => canvas.into(),
+        }
+    }
+}
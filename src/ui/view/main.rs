@@ -1,19 +1,23 @@
 //! Main page of program
 
+use std::time::Instant;
+
 use iced::{
     Alignment::Center,
-    Element, Theme,
+    Color, Element, Theme,
     alignment::Horizontal,
     widget::{
         Column, Row, Text, button, center, column, container, horizontal_rule, horizontal_space,
-        row, scrollable, text, tooltip::Position,
+        pick_list, row, scrollable, text, text_input, tooltip::Position,
     },
 };
 
 use crate::{
     fl,
+    pause::PauseReason,
     stats::StatisticEntry,
-    time::{Time, fmt_date},
+    time::fmt_date,
+    timer::PhaseOutcome,
     ui::{
         utils,
         widget::{text_small, txt_tooltip},
@@ -23,7 +27,11 @@ use crate::{
 use super::{Message, SMALL_TEXT_SIZE, TimeKeeper};
 
 impl TimeKeeper {
-    pub fn main_page(&self) -> Element<Message> {
+    pub fn main_page(&self) -> Element<'_, Message> {
+        if self.micro.is_break() {
+            return self.micro_break_page();
+        }
+
         /* Максимальное число элементов вектора - 3 (область таймера,
          * область статистики и нижние кнопки "О программе", "Настройки",
          * "Статистика"). Однако это количество может быть и меньше 3,
@@ -33,13 +41,22 @@ impl TimeKeeper {
          */
         let mut layout_items: Vec<Element<Message>> = Vec::with_capacity(3);
 
-        let timer = column![self.time_text(), self.time_buttons(),]
+        let mut timer = column![self.time_text(), self.time_buttons(), self.phase_buttons()]
             .align_x(Center)
             .spacing(10);
+        if let Some(pause) = self.pause_text() {
+            timer = timer.push(pause);
+        }
+        if !self.is_strict_break() {
+            timer = timer.push(self.profile_picker());
+        }
+        if self.is_strict_break() && !self.conf.emergency_phrase.trim().is_empty() {
+            timer = timer.push(self.emergency_skip_input());
+        }
         layout_items.push(center(timer).into());
 
         if self.show_stats {
-            layout_items.push(self.stats_subpage().into());
+            layout_items.push(self.stats_subpage());
         }
 
         let stats_btn_txt = match self.show_stats {
@@ -48,45 +65,184 @@ impl TimeKeeper {
         };
         layout_items.push(self.footer_buttons(stats_btn_txt).into());
 
+        // У фазы из последовательности может быть свой цвет фона
+        let color = self
+            .timer
+            .phase()
+            .and_then(|phase| phase.color.as_deref())
+            .and_then(Color::parse);
         container(Column::with_children(layout_items))
-            .style(move |style: &Theme| utils::get_container_style(style, self.is_work))
+            .style(move |style: &Theme| match color {
+                Some(color) => utils::get_colored_container_style(color),
+                None => utils::get_container_style(style, self.timer.is_work()),
+            })
             .into()
     }
 
-    fn get_sub_time(&self) -> u16 {
-        match self.is_work {
-            true => self.conf.work_time,
-            false => self.conf.free_time,
-        }
+    /// Compact countdown shown during an eye micro-break (also in the small
+    /// window on top of all windows)
+    fn micro_break_page(&self) -> Element<'_, Message> {
+        let countdown = column![
+            text(fl!("micro_break")),
+            text(self.micro.remaining_time(Instant::now()).to_string()).size(25),
+        ]
+        .align_x(Center)
+        .spacing(5);
+
+        container(center(countdown))
+            .style(|style: &Theme| utils::get_container_style(style, false))
+            .into()
     }
 
-    fn time_text(&self) -> Text {
-        text(format!(
-            "{} | {}",
-            match self.is_work {
-                true => fl!("work"),
-                false => fl!("break"),
+    fn time_text(&self) -> Text<'_> {
+        if self.off_hours {
+            return text(fl!("off_hours"));
+        }
+
+        let label = match self.timer.phase() {
+            Some(phase) => phase.name.clone(),
+            None => match (self.timer.is_work(), self.timer.is_long_break()) {
+                (true, _) => fl!("work"),
+                (false, false) => fl!("break"),
+                (false, true) => fl!("long_break"),
             },
-            Time::from_secs(self.get_sub_time() - self.elapsed_time),
+        };
+        let label = match self.timer.cycle() {
+            Some((cycle, every)) => format!("{label} {cycle}/{every}"),
+            None => label,
+        };
+
+        text(format!(
+            "{label} | {}",
+            self.timer.remaining_time(Instant::now()),
         ))
     }
 
-    fn time_buttons(&self) -> Row<Message> {
-        let label = match self.is_pause {
+    fn time_buttons(&self) -> Row<'_, Message> {
+        let label = match self.timer.is_pause() {
             true => fl!("start"),
             false => fl!("pause"),
         };
 
+        // Строгий перерыв нельзя ни остановить, ни поставить на паузу
+        let is_free = !self.is_strict_break();
+        let mut buttons = row![
+            button(text(label)).on_press_maybe(is_free.then_some(Message::StartButtonPressed)),
+            button(text(fl!("stop"))).on_press_maybe(is_free.then_some(Message::StopButtonPressed)),
+        ]
+        .spacing(5);
+
+        // Паузу с причиной можно выбрать, только пока таймер идёт
+        if is_free && !self.timer.is_pause() {
+            buttons = buttons.push(txt_tooltip(
+                pick_list(
+                    PauseReason::ALL,
+                    None::<PauseReason>,
+                    Message::PauseSelected,
+                )
+                .placeholder(fl!("pause_reason"))
+                .text_size(SMALL_TEXT_SIZE)
+                .padding(3),
+                fl!("pause_reason_tooltip"),
+                Position::Bottom,
+            ));
+        }
+
+        // Отложить можно только перерыв, и не больше заданного числа раз
+        if !self.timer.is_work() && self.conf.max_snoozes > 0 {
+            let postpone = button(text(fl!("postpone")))
+                .style(button::secondary)
+                .on_press_maybe(
+                    (is_free && self.timer.can_postpone())
+                        .then_some(Message::PostponeButtonPressed),
+                );
+            let (mins, left) = (
+                self.timer.snooze_time().to_secs() / 60,
+                self.timer.snoozes_left(),
+            );
+            buttons = buttons.push(txt_tooltip(
+                postpone,
+                fl!("postpone_tooltip", mins = mins, left = left),
+                Position::Bottom,
+            ));
+        }
+
+        buttons
+    }
+
+    /// Why the timer is paused and when the timed pause ends
+    fn pause_text(&self) -> Option<Text<'_>> {
+        let (reason, _) = self.pause?;
+        let label = match self.timer.pause_left(Instant::now()) {
+            Some(left) => {
+                let time = left.to_string();
+                fl!("paused_for", reason = reason.to_string(), time = time)
+            }
+            None => reason.to_string(),
+        };
+        Some(text_small(label))
+    }
+
+    /// Manual phase control: end the current phase right away or make it
+    /// longer
+    fn phase_buttons(&self) -> Row<'_, Message> {
+        let (skip_label, skip_tooltip) = match self.timer.is_work() {
+            true => (fl!("break_now"), fl!("break_now_tooltip")),
+            false => (fl!("end_break"), fl!("end_break_tooltip")),
+        };
+
         row![
-            button(text(label)).on_press(Message::StartButtonPressed),
-            button(text(fl!("stop"))).on_press(Message::StopButtonPressed),
+            txt_tooltip(
+                button(text_small(skip_label))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (!self.is_strict_break()).then_some(Message::SkipButtonPressed)
+                    ),
+                skip_tooltip,
+                Position::Bottom,
+            ),
+            txt_tooltip(
+                button(text_small(fl!("extend")))
+                    .style(button::secondary)
+                    .on_press(Message::ExtendButtonPressed),
+                fl!("extend_tooltip"),
+                Position::Bottom,
+            ),
         ]
         .spacing(5)
     }
 
-    fn stats_info(&self, entry: StatisticEntry) -> Element<Message> {
+    fn profile_picker(&self) -> Element<'_, Message> {
+        let profiles = self.conf.all_profiles();
+        let selected = profiles
+            .iter()
+            .find(|profile| profile.name == self.conf.profile)
+            .or(profiles.first())
+            .cloned();
+
+        txt_tooltip(
+            pick_list(profiles, selected, Message::ProfileSelected)
+                .text_size(SMALL_TEXT_SIZE)
+                .padding(3),
+            fl!("profile_tooltip"),
+            Position::Bottom,
+        )
+        .into()
+    }
+
+    /// Field for the emergency phrase: the only way to skip a strict break
+    fn emergency_skip_input(&self) -> Element<'_, Message> {
+        text_input(&fl!("emergency_skip_placeholder"), &self.emergency_input)
+            .on_input(Message::EmergencyInputChanged)
+            .on_submit(Message::EmergencySkipSubmitted)
+            .size(SMALL_TEXT_SIZE)
+            .width(200)
+            .into()
+    }
+
+    fn stats_info(&self, entry: &StatisticEntry) -> Element<'_, Message> {
         let hcolor = utils::get_dimmed_text_color(&self.theme());
-        let headers = column![
+        let mut headers = column![
             text(fl!("stats_date")).color(hcolor),
             text(fl!("stats_type")).color(hcolor),
             text(fl!("stats_duration")).color(hcolor),
@@ -94,21 +250,31 @@ impl TimeKeeper {
         .spacing(5)
         .align_x(Horizontal::Right);
 
-        let values = column![
-            text(fmt_date(entry.date)),
-            text(if entry.is_wtime {
-                fl!("work")
-            } else {
-                fl!("break")
+        let mut values = column![
+            text(fmt_date(entry.end)),
+            text(match (entry.is_wtime, entry.is_long_break) {
+                _ if !entry.phase.is_empty() => entry.phase.clone(),
+                (true, _) => fl!("work"),
+                (false, false) => fl!("break"),
+                (false, true) => fl!("long_break"),
             }),
-            text(Time::from_secs(entry.time).to_string()),
+            text(entry.time.to_string()),
         ]
         .spacing(5);
 
+        if !entry.profile.is_empty() {
+            headers = headers.push(text(fl!("stats_profile")).color(hcolor));
+            values = values.push(text(entry.profile.clone()));
+        }
+        if entry.outcome != PhaseOutcome::Finished {
+            headers = headers.push(text(fl!("stats_outcome")).color(hcolor));
+            values = values.push(text(entry.outcome.to_string()));
+        }
+
         row![headers, values].spacing(5).into()
     }
 
-    fn stats_subpage(&self) -> Element<Message> {
+    fn stats_subpage(&self) -> Element<'_, Message> {
         let mut elements = column![].spacing(5).align_x(Center);
 
         let is_empty_stats = self.stats.is_empty();
@@ -124,7 +290,7 @@ impl TimeKeeper {
             let mut count = 10;
 
             while len > 0 && count > 0 {
-                elements = elements.push(self.stats_info(self.stats.stats[len - 1]));
+                elements = elements.push(self.stats_info(&self.stats.stats[len - 1]));
                 elements = elements.push(horizontal_rule(0));
 
                 len -= 1;
@@ -164,8 +330,8 @@ impl TimeKeeper {
          * окна, нам нужно скрывать нижнюю область кнопок, показывая
          * только таймер.
          *
-         * Если мы отдыхаем (!self.is_work) и у нас включено
-         * отображение модального окна (!self.conf_desktop_notifications),
+         * Если мы отдыхаем (!self.timer.is_work()) и у нас включено
+         * отображение модального окна (self.has_break_window()),
          * мы отображаем только статистику. В противном случае отображаем
          * полный блок нижних кнопок.
          */
@@ -176,20 +342,23 @@ impl TimeKeeper {
             button(text_small(fl!("about")))
                 .style(button::text)
                 .on_press(Message::AboutButtonPressed),
+            button(text_small(fl!("dashboard")))
+                .style(button::text)
+                .on_press(Message::DashboardButtonPressed),
             horizontal_space(),
             button(text_small(stats_btn_txt.clone()))
                 .style(button::text)
                 .on_press(Message::ShowStatsButtonPressed),
         ];
 
-        match self.is_work {
+        match self.timer.is_work() {
             // Отображаем стандартный блок кнопок если работаем
             true => default_footer_buttons,
 
             // Если не работаем - смотрим, что у нас: уведомления или
             // модальное окно
             false => {
-                if self.conf.desktop_notifications {
+                if !self.has_break_window() {
                     default_footer_buttons
                 } else {
                     row![
@@ -13,13 +13,13 @@ use iced::{
     Alignment::Center,
     Element, Length,
     widget::{
-        button, column, container, horizontal_rule, horizontal_space, row, text, toggler, tooltip,
-        vertical_space,
+        button, column, container, horizontal_rule, horizontal_space, row, scrollable, text,
+        toggler, tooltip,
     },
 };
 
 impl TimeKeeper {
-    pub fn settings_page(&self) -> Element<Message> {
+    pub fn settings_page(&self) -> Element<'_, Message> {
         let header = header(fl!("pref_header"));
 
         #[cfg(unix)]
@@ -62,9 +62,11 @@ impl TimeKeeper {
         .align_y(Center)
         .spacing(5);
 
-        let layout = column![
-            header,
+        // Все настройки не помещаются в маленькое окно программы, поэтому
+        // прокручиваем их, оставляя на месте заголовок и кнопку "ОК"
+        let prefs = column![
             self.time_edit_box(),
+            self.suggestion_edit_box(),
             row![text(fl!("pref_break_alerts")), horizontal_rule(0),]
                 .spacing(5)
                 .align_y(Center),
@@ -76,7 +78,20 @@ impl TimeKeeper {
                 tooltip::Position::Top,
             ),
             autostart_row,
-            vertical_space().height(Length::Fill),
+            self.warning_edit_box(),
+            self.postpone_edit_box(),
+            self.daily_limit_edit_box(),
+            self.strict_mode_edit_box(),
+            self.escalation_edit_box(),
+            self.micro_break_edit_box(),
+            self.schedule_edit_box(),
+            self.idle_edit_box(),
+        ]
+        .spacing(5);
+
+        let layout = column![
+            header,
+            scrollable(prefs).spacing(5).height(Length::Fill),
             button(text(fl!("pref_close"))).on_press(Message::SettingsButtonPressed),
         ]
         .spacing(5);
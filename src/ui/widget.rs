@@ -1,11 +1,14 @@
 //! Custom widgets for TimeKeeper
 
+use std::ops::RangeInclusive;
+
 use iced::border::Radius;
 use iced::widget::slider::Rail;
 use iced::widget::text::IntoFragment;
 use iced::widget::tooltip::Position;
 use iced::widget::{
-    Button, Container, Text, Tooltip, button, column, container, horizontal_rule, row, slider, text,
+    Button, Container, Slider, Text, Tooltip, button, column, container, horizontal_rule,
+    horizontal_space, pick_list, row, slider, text, text_input, toggler,
 };
 use iced::{Alignment::Center, Element};
 use iced::{Color, Theme};
@@ -13,28 +16,41 @@ use iced::{Color, Theme};
 use super::{
     Message, TimeKeeper,
     colors::{FREE_TIME_SLIDER_COLOR, URL_BUTTON_COLOR, WORK_TIME_SLIDER_COLOR},
+    utils,
+};
+use crate::{
+    aggregate::WeekStart,
+    consts::{SUGGEST_BREAK_RANGE, SUGGEST_WORK_RANGE},
+    fl,
+    idle::IdleBackend,
+    time::Time,
+    timer::IntervalPolicy,
 };
-use crate::{fl, time::Time};
 
+#[derive(Clone, Copy)]
 pub enum TimeType {
     Work,
     Free,
 }
 
 impl TimeKeeper {
-    pub fn time_edit_box(&self) -> Element<Message> {
-        let wtime_slider = slider(1800..=10800, self.wtime.to_secs(), Message::WTimeChanged)
-            .step(600u16)
-            .shift_step(60u16)
-            .style(|theme: &Theme, status: slider::Status| {
-                slider_style(TimeType::Work, theme, status)
-            });
-        let ftime_slider = slider(60..=1800, self.ftime.to_secs(), Message::FTimeChanged)
-            .step(60u16)
-            .shift_step(600u16)
-            .style(|theme: &Theme, status: slider::Status| {
-                slider_style(TimeType::Free, theme, status)
-            });
+    pub fn time_edit_box(&self) -> Element<'_, Message> {
+        let wtime_slider = time_slider(
+            SUGGEST_WORK_RANGE,
+            self.wtime,
+            Message::WTimeChanged,
+            TimeType::Work,
+        )
+        .step(600u32)
+        .shift_step(60u32);
+        let ftime_slider = time_slider(
+            SUGGEST_BREAK_RANGE,
+            self.ftime,
+            Message::FTimeChanged,
+            TimeType::Free,
+        )
+        .step(60u32)
+        .shift_step(600u32);
 
         column![
             column![
@@ -51,7 +67,7 @@ impl TimeKeeper {
                 row![
                     wtime_slider,
                     txt_tooltip(
-                        time_box(Time::from(self.wtime)),
+                        time_box(self.wtime),
                         fl!("pref_work_change"),
                         Position::Bottom
                     ),
@@ -73,7 +89,7 @@ impl TimeKeeper {
                 row![
                     ftime_slider,
                     txt_tooltip(
-                        time_box(Time::from(self.ftime)),
+                        time_box(self.ftime),
                         fl!("pref_break_change"),
                         Position::Bottom
                     ),
@@ -81,14 +97,575 @@ impl TimeKeeper {
                 .spacing(5)
                 .align_y(Center)
             ],
+            self.long_break_edit_box(),
+            row![
+                txt_tooltip(
+                    text(fl!("pref_interval_policy")).size(12),
+                    fl!("pref_interval_policy_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_space(),
+                pick_list(
+                    IntervalPolicy::ALL,
+                    Some(self.conf.interval_policy),
+                    Message::IntervalPolicySelected,
+                )
+                .text_size(12)
+                .padding(3),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
+
+impl TimeKeeper {
+    /// Intervals suggested by the statistics and their weekly automatic
+    /// adjustment
+    pub fn suggestion_edit_box(&self) -> Element<'_, Message> {
+        let adjust = &self.conf.auto_adjust;
+        let bound_slider = |range, value, msg: fn(Time) -> Message, time_type| {
+            time_slider(range, value, msg, time_type)
+                .step(300u32)
+                .shift_step(60u32)
+        };
+
+        let mut suggestion = column![
+            row![
+                txt_tooltip(
+                    text(fl!("pref_suggest")),
+                    fl!("pref_suggest_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5);
+
+        suggestion = match &self.suggestion {
+            Some(sug) => {
+                let work = sug.work_time.to_string_without_secs();
+                let free = sug.free_time.to_string_without_secs();
+                let mut reasons = column![].spacing(2);
+                for reason in &sug.reasons {
+                    reasons = reasons.push(text_small(format!("• {reason}")));
+                }
+
+                suggestion
+                    .push(
+                        row![
+                            text(fl!("pref_suggest_intervals", work = work, free = free)),
+                            horizontal_space(),
+                            button(text(fl!("pref_suggest_apply")).size(12))
+                                .padding(3)
+                                .on_press(Message::SuggestionApplied),
+                        ]
+                        .spacing(5)
+                        .align_y(Center),
+                    )
+                    .push(reasons)
+            }
+            None => suggestion.push(text_small(fl!("pref_suggest_none"))),
+        };
+
+        suggestion
+            .push(
+                toggler(adjust.enabled)
+                    .label(fl!("pref_auto_adjust"))
+                    .on_toggle(Message::AutoAdjustToggled),
+            )
+            .push(
+                row![
+                    text(fl!("pref_auto_adjust_work")).size(12),
+                    bound_slider(
+                        SUGGEST_WORK_RANGE,
+                        adjust.min_work_time,
+                        Message::AutoAdjustMinWorkChanged,
+                        TimeType::Work,
+                    ),
+                    bound_slider(
+                        SUGGEST_WORK_RANGE,
+                        adjust.max_work_time,
+                        Message::AutoAdjustMaxWorkChanged,
+                        TimeType::Work,
+                    ),
+                    value_box(format!(
+                        "{}-{}",
+                        adjust.min_work_time.to_string_without_secs(),
+                        adjust.max_work_time.to_string_without_secs(),
+                    )),
+                ]
+                .spacing(5)
+                .align_y(Center),
+            )
+            .push(
+                row![
+                    text(fl!("pref_auto_adjust_break")).size(12),
+                    bound_slider(
+                        SUGGEST_BREAK_RANGE,
+                        adjust.min_free_time,
+                        Message::AutoAdjustMinBreakChanged,
+                        TimeType::Free,
+                    ),
+                    bound_slider(
+                        SUGGEST_BREAK_RANGE,
+                        adjust.max_free_time,
+                        Message::AutoAdjustMaxBreakChanged,
+                        TimeType::Free,
+                    ),
+                    value_box(format!(
+                        "{}-{}",
+                        adjust.min_free_time.to_string_without_secs(),
+                        adjust.max_free_time.to_string_without_secs(),
+                    )),
+                ]
+                .spacing(5)
+                .align_y(Center),
+            )
+            .into()
+    }
+
+    fn long_break_edit_box(&self) -> Element<'_, Message> {
+        let time_slider = time_slider(
+            300..=3600,
+            self.conf.long_break_time,
+            Message::LongBreakTimeChanged,
+            TimeType::Free,
+        )
+        .step(300u32)
+        .shift_step(60u32);
+        let every_slider = slider(
+            0..=10,
+            self.conf.long_break_every,
+            Message::LongBreakEveryChanged,
+        )
+        .style(|theme: &Theme, status: slider::Status| slider_style(TimeType::Free, theme, status));
+        let every = match self.conf.long_break_every {
+            0 => fl!("pref_off"),
+            every => every.to_string(),
+        };
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("long_break")),
+                    fl!("pref_long_break_slider_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                time_slider,
+                txt_tooltip(
+                    time_box(self.conf.long_break_time),
+                    fl!("pref_long_break_change"),
+                    Position::Bottom
+                ),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                text(fl!("pref_long_break_every")).size(12),
+                every_slider,
+                container(text(every)).padding(3),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .into()
+    }
+
+    pub fn warning_edit_box(&self) -> Element<'_, Message> {
+        let early_slider = time_slider(
+            0..=900,
+            self.conf.break_warning,
+            Message::BreakWarningChanged,
+            TimeType::Work,
+        )
+        .step(60u32);
+        let final_slider = time_slider(
+            0..=120,
+            self.conf.break_final_warning,
+            Message::BreakFinalWarningChanged,
+            TimeType::Work,
+        )
+        .step(10u32);
+        let warning_value = |time: Time| match time.is_zero() {
+            true => fl!("pref_off"),
+            false => time.to_string(),
+        };
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("pref_warnings")),
+                    fl!("pref_warnings_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                text(fl!("pref_warning_early")).size(12),
+                early_slider,
+                value_box(warning_value(self.conf.break_warning)),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                text(fl!("pref_warning_final")).size(12),
+                final_slider,
+                value_box(warning_value(self.conf.break_final_warning)),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn daily_limit_edit_box(&self) -> Element<'_, Message> {
+        let limit_slider = time_slider(
+            0..=43200,
+            self.conf.daily_limit,
+            Message::DailyLimitChanged,
+            TimeType::Work,
+        )
+        .step(1800u32);
+        let rollover_slider = slider(
+            0..=23,
+            self.conf.day_rollover_hour,
+            Message::DayRolloverHourChanged,
+        )
+        .style(|theme: &Theme, status: slider::Status| slider_style(TimeType::Free, theme, status));
+        let limit = match self.conf.daily_limit.is_zero() {
+            true => fl!("pref_off"),
+            false => self.conf.daily_limit.to_string_without_secs(),
+        };
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("pref_daily_limit")),
+                    fl!("pref_daily_limit_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![limit_slider, value_box(limit)]
+                .spacing(5)
+                .align_y(Center),
+            toggler(self.conf.daily_limit_lock)
+                .label(fl!("pref_daily_limit_lock"))
+                .on_toggle(Message::DailyLimitLockToggled),
+            row![
+                text(fl!("pref_day_rollover")).size(12),
+                rollover_slider,
+                value_box(format!("{:02}:00", self.conf.day_rollover_hour)),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                text(fl!("pref_week_start")).size(12),
+                horizontal_space(),
+                pick_list(
+                    WeekStart::ALL,
+                    Some(self.conf.week_start),
+                    Message::WeekStartSelected,
+                )
+                .text_size(12)
+                .padding(3),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn postpone_edit_box(&self) -> Element<'_, Message> {
+        let snooze_slider = time_slider(
+            60..=1800,
+            self.conf.snooze_time,
+            Message::SnoozeTimeChanged,
+            TimeType::Work,
+        )
+        .step(60u32);
+        let max_slider = slider(0..=5, self.conf.max_snoozes, Message::MaxSnoozesChanged).style(
+            |theme: &Theme, status: slider::Status| slider_style(TimeType::Free, theme, status),
+        );
+        let max_snoozes = match self.conf.max_snoozes {
+            0 => fl!("pref_off"),
+            max => max.to_string(),
+        };
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("postpone")),
+                    fl!("pref_postpone_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                text(fl!("pref_postpone_by")).size(12),
+                snooze_slider,
+                time_box(self.conf.snooze_time),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                text(fl!("pref_postpone_max")).size(12),
+                max_slider,
+                container(text(max_snoozes)).padding(3),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn micro_break_edit_box(&self) -> Element<'_, Message> {
+        let interval_slider = time_slider(
+            300..=3600,
+            self.conf.micro_break_interval,
+            Message::MicroBreakIntervalChanged,
+            TimeType::Work,
+        )
+        .step(300u32)
+        .shift_step(60u32);
+        let time_slider = time_slider(
+            10..=120,
+            self.conf.micro_break_time,
+            Message::MicroBreakTimeChanged,
+            TimeType::Free,
+        )
+        .step(5u32);
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("micro_break")),
+                    fl!("pref_micro_break_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            toggler(self.conf.micro_breaks)
+                .label(fl!("pref_micro_breaks"))
+                .on_toggle(Message::MicroBreaksToggled),
+            toggler(self.conf.micro_break_overlay)
+                .label(fl!("pref_micro_break_overlay"))
+                .on_toggle(Message::MicroBreakOverlayToggled),
+            row![
+                text(fl!("pref_micro_break_every")).size(12),
+                interval_slider,
+                time_box(self.conf.micro_break_interval),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            row![
+                text(fl!("pref_micro_break_for")).size(12),
+                time_slider,
+                value_box(self.conf.micro_break_time.to_string()),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn strict_mode_edit_box(&self) -> Element<'_, Message> {
+        // Во время строгого перерыва его настройки менять нельзя
+        let is_free = !self.is_strict_break();
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("pref_strict")),
+                    fl!("pref_strict_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            toggler(self.conf.strict_mode)
+                .label(fl!("pref_strict_mode"))
+                .on_toggle_maybe(is_free.then_some(Message::StrictModeToggled)),
+            row![
+                text(fl!("pref_emergency_phrase")).size(12),
+                txt_tooltip(
+                    text_input("", &self.conf.emergency_phrase)
+                        .on_input_maybe(is_free.then_some(Message::EmergencyPhraseChanged))
+                        .size(12)
+                        .padding(3),
+                    fl!("pref_emergency_phrase_tooltip"),
+                    Position::Bottom
+                ),
+            ]
+            .spacing(5)
+            .align_y(Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn escalation_edit_box(&self) -> Element<'_, Message> {
+        let escalation = &self.conf.escalation;
+        let step_slider = |value: Time, on_change: fn(Time) -> Message| {
+            time_slider(0..=600, value, on_change, TimeType::Free).step(30u32)
+        };
+        let step_value = |time: Time| match time.is_zero() {
+            true => fl!("pref_off"),
+            false => time.to_string(),
+        };
+        let step_row = |label: String, value: Time, on_change: fn(Time) -> Message| {
+            row![
+                text(label).size(12),
+                step_slider(value, on_change),
+                value_box(step_value(value)),
+            ]
+            .spacing(5)
+            .align_y(Center)
+        };
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("pref_escalation")),
+                    fl!("pref_escalation_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            toggler(escalation.enabled)
+                .label(fl!("pref_escalation_enabled"))
+                .on_toggle(Message::EscalationToggled),
+            step_row(
+                fl!("escalation_critical"),
+                escalation.critical_after,
+                Message::EscalationCriticalChanged
+            ),
+            step_row(
+                fl!("escalation_window"),
+                escalation.window_after,
+                Message::EscalationWindowChanged
+            ),
+            step_row(
+                fl!("escalation_lock"),
+                escalation.lock_after,
+                Message::EscalationLockChanged
+            ),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn schedule_edit_box(&self) -> Element<'_, Message> {
+        let hcolor = utils::get_dimmed_text_color(&self.theme());
+        let mut ranges = column![].spacing(3);
+        for range in &self.conf.schedule.ranges {
+            ranges = ranges.push(text(range.to_string()).size(12).color(hcolor));
+        }
+
+        column![
+            row![
+                txt_tooltip(
+                    text(fl!("pref_schedule")),
+                    fl!("pref_schedule_tooltip"),
+                    Position::Bottom
+                ),
+                horizontal_rule(0),
+            ]
+            .spacing(5)
+            .align_y(Center),
+            toggler(self.conf.schedule.enabled)
+                .label(fl!("pref_schedule_enabled"))
+                .on_toggle(Message::ScheduleToggled),
+            ranges,
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn idle_edit_box(&self) -> Element<'_, Message> {
+        let backend = pick_list(
+            IdleBackend::ALL,
+            Some(self.conf.idle_backend),
+            Message::IdleBackendSelected,
+        )
+        .text_size(12)
+        .padding(3);
+        let threshold_slider = time_slider(
+            60..=1800,
+            self.conf.idle_threshold,
+            Message::IdleThresholdChanged,
+            TimeType::Free,
+        )
+        .step(60u32)
+        .shift_step(600u32);
+
+        column![
+            row![text(fl!("pref_idle")), horizontal_rule(0)]
+                .spacing(5)
+                .align_y(Center),
+            row![text(fl!("pref_idle_backend")), horizontal_space(), backend]
+                .spacing(5)
+                .align_y(Center),
+            row![
+                threshold_slider,
+                txt_tooltip(
+                    time_box(self.conf.idle_threshold),
+                    fl!("pref_idle_threshold_tooltip"),
+                    Position::Bottom
+                ),
+            ]
+            .spacing(5)
+            .align_y(Center),
         ]
         .spacing(5)
         .into()
     }
 }
 
+/// Slider over a duration. `range` is in seconds
+fn time_slider<'a>(
+    range: RangeInclusive<u32>,
+    value: Time,
+    on_change: fn(Time) -> Message,
+    time_type: TimeType,
+) -> Slider<'a, u32, Message> {
+    slider(range, value.to_secs(), move |secs| {
+        on_change(Time::from_secs(secs))
+    })
+    .style(move |theme: &Theme, status: slider::Status| slider_style(time_type, theme, status))
+}
+
 fn time_box<'a>(time: Time) -> Container<'a, Message> {
-    container(text(time.to_string_without_secs()))
+    value_box(time.to_string_without_secs())
+}
+
+fn value_box<'a>(value: String) -> Container<'a, Message> {
+    container(text(value))
         .style(|style: &Theme| {
             let palette = style.extended_palette();
             container::Style {